@@ -0,0 +1,205 @@
+//! The letters words are made of, and which bit of a [`Charset`] each of them is.
+
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+use std::{
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::{
+    charset::{Charset, WideCharset},
+    sentence::Sentence,
+    word::{Word, MAX_WORD_LEN},
+};
+
+/// The alphabets which can be chosen by name, with their letters in alphabetical order.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("en", "abcdefghijklmnopqrstuvwxyz"),
+    ("de", "abcdefghijklmnopqrstuvwxyzäöüß"),
+    ("es", "abcdefghijklmnñopqrstuvwxyz"),
+    ("pl", "aąbcćdeęfghijklłmnńoóprsśtuwyzźż"),
+    ("ru", "абвгдеёжзийклмнопрстуфхцчшщъыьэюя"),
+    ("uk", "абвгґдеєжзиіїйклмнопрстуфхцчшщьюя"),
+    ("el", "αβγδεζηθικλμνξοπρστυφχψω"),
+];
+
+/// An ordered set of lowercase letters. Each letter's position is its bit in a [`Charset`] and the value
+/// stored for it in a [`Word`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: Vec<char>,
+    indices: FxHashMap<char, u8>,
+    /// Letters which are written differently at the end of a word, and how, e.g. Greek sigma as `ς`.
+    finals: FxHashMap<u8, char>,
+}
+
+impl Alphabet {
+    /// The 26 letters of the English alphabet, which is what [`crate::word::parse`] assumes.
+    pub fn english() -> Self {
+        Self::new(PRESETS[0].1).unwrap()
+    }
+
+    /// An alphabet of the given letters in order, which are lowercased.
+    pub fn new(letters: &str) -> Result<Self, AlphabetError> {
+        let mut alphabet = Alphabet {
+            letters: vec![],
+            indices: FxHashMap::default(),
+            finals: FxHashMap::default(),
+        };
+        for letter in letters.chars().flat_map(char::to_lowercase) {
+            if alphabet.indices.contains_key(&letter) {
+                return Err(AlphabetError::Repeated(letter));
+            }
+            alphabet
+                .indices
+                .insert(letter, alphabet.letters.len() as u8);
+            alphabet.letters.push(letter);
+            if alphabet.len() > WideCharset::CAPACITY {
+                return Err(AlphabetError::TooLong);
+            }
+        }
+        if alphabet.is_empty() {
+            return Err(AlphabetError::Empty);
+        }
+        Ok(alphabet)
+    }
+
+    /// One of the [`PRESETS`], by name.
+    pub fn named(name: &str) -> Option<Self> {
+        let (_, letters) = PRESETS.iter().find(|(n, _)| *n == name)?;
+        let mut alphabet = Self::new(letters).unwrap();
+        if name == "el" {
+            // Final sigma is just how sigma is written at the end of a word.
+            let sigma = alphabet.indices[&'σ'];
+            alphabet.indices.insert('ς', sigma);
+            alphabet.finals.insert(sigma, 'ς');
+        }
+        Some(alphabet)
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    /// Whether `c` is a letter of the alphabet, in either case.
+    pub fn contains(&self, c: char) -> bool {
+        c.to_lowercase().all(|l| self.indices.contains_key(&l))
+    }
+
+    /// Every letter of the alphabet.
+    pub fn charset<C: Charset>(&self) -> C {
+        C::first(self.len())
+    }
+
+    /// The position of `letter` in the alphabet, if it's in it.
+    pub fn index(&self, letter: char) -> Option<u8> {
+        self.indices.get(&letter).copied()
+    }
+
+    /// Parse a line of a word list into a [`Word`], or `None` if it has anything other than letters of the
+    /// alphabet (in either case) or is too long to have distinct letters.
+    pub fn parse(&self, line: &str) -> Option<Word> {
+        let mut letters = Vec::with_capacity(MAX_WORD_LEN);
+        for c in line.chars().flat_map(char::to_lowercase) {
+            letters.push(self.index(c)?);
+        }
+        Word::from_letters(&letters)
+    }
+
+    /// The letters of `w`, with the last one in its final form if it has one.
+    pub fn show(&self, w: Word) -> String {
+        let letters = w.letters();
+        let mut shown: String = letters.iter().map(|&l| self.letters[l as usize]).collect();
+        if let Some(&last) = letters.last().and_then(|l| self.finals.get(l)) {
+            shown.pop();
+            shown.push(last);
+        }
+        shown
+    }
+
+    /// The words of `sentence`, separated by spaces.
+    pub fn show_sentence<const N: usize>(&self, sentence: &Sentence<N>) -> String {
+        let words: Vec<_> = sentence.words().map(|w| self.show(w)).collect();
+        words.join(" ")
+    }
+
+    /// The letters of `charset`, in alphabetical order.
+    pub fn show_charset(&self, charset: impl Charset) -> String {
+        charset
+            .letters()
+            .map(|l| self.letters[l as usize])
+            .collect()
+    }
+}
+
+impl Hash for Alphabet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The indices say everything about the alphabet, but have to be put in order first.
+        self.indices.iter().sorted().for_each(|i| i.hash(state));
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = AlphabetError;
+
+    /// Either the name of one of the [`PRESETS`], or the letters of the alphabet in order.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::named(s) {
+            Some(alphabet) => Ok(alphabet),
+            None => Self::new(s),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphabetError {
+    Empty,
+    Repeated(char),
+    /// More letters than the widest [`Charset`] can hold.
+    TooLong,
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::Empty => write!(f, "an alphabet needs at least one letter"),
+            AlphabetError::Repeated(letter) => write!(f, "`{letter}` is in the alphabet twice"),
+            AlphabetError::TooLong => write!(
+                f,
+                "an alphabet can have at most {} letters",
+                WideCharset::CAPACITY
+            ),
+        }
+    }
+}
+
+impl Error for AlphabetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_sigma() {
+        let greek = Alphabet::named("el").unwrap();
+        for (word, shown) in [("μυς", "μυς"), ("μυσ", "μυς"), ("σωμα", "σωμα")]
+        {
+            let w = greek.parse(word).unwrap();
+            assert_eq!(greek.show(w), shown);
+        }
+        assert_eq!(greek.parse("μυς"), greek.parse("μυσ"));
+    }
+}
@@ -0,0 +1,210 @@
+//! An alternative to [`WordGraph`](crate::WordGraph) which numbers the words densely and keeps which ones can go
+//! together as a bit matrix, so that the words which could extend a partial solution are found by ANDing rows
+//! together rather than filtering lists of charsets.
+//!
+//! The matrix takes a bit for every pair of words, so it's best suited to lists of up to a few tens of thousands
+//! of anagram classes.
+
+use rayon::prelude::*;
+use std::sync::Arc;
+
+use crate::{
+    charset::{Charset, LowerAsciiCharset},
+    graph::{is_sol, Goal, Sharing},
+    sentence::CharsetSentence,
+    solver::{branch, Split},
+};
+
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+pub struct BitsetGraph<C = LowerAsciiCharset> {
+    /// The words in increasing order, so that only ever adding words with higher indices finds each set once.
+    words: Vec<C>,
+    /// Row `i` has bit `j` set if word `j` can go with word `i`, in blocks of 64 bits.
+    rows: Vec<u64>,
+    blocks: usize,
+    sharing: Sharing,
+}
+
+impl<C: Charset> BitsetGraph<C> {
+    pub fn new(words: Vec<C>) -> Self {
+        Self::with_sharing(words, Sharing::default())
+    }
+
+    /// Like [`Self::new`], but the solutions can share as many letters as `sharing` allows.
+    pub fn with_sharing(mut words: Vec<C>, sharing: Sharing) -> Self {
+        words.sort_unstable();
+        words.dedup();
+        let blocks = words.len().div_ceil(BLOCK_BITS);
+        let max_shared = sharing.max_pair();
+        let mut rows = vec![0; words.len() * blocks];
+        if blocks > 0 {
+            rows.par_chunks_mut(blocks)
+                .zip(words.par_iter())
+                .for_each(|(row, &charset)| {
+                    for (j, &c) in words.iter().enumerate() {
+                        if c != charset && charset.shared(c) <= max_shared {
+                            row[j / BLOCK_BITS] |= 1 << (j % BLOCK_BITS);
+                        }
+                    }
+                });
+        }
+        Self {
+            words,
+            rows,
+            blocks,
+            sharing,
+        }
+    }
+
+    pub fn sharing(&self) -> Sharing {
+        self.sharing
+    }
+
+    /// Find every set of words that share no letters (or as many as [`Self::sharing`] allows) and satisfy
+    /// `goal`, modulo anagram.
+    pub fn search<const N: usize>(&self, goal: Goal) -> Vec<CharsetSentence<N, C>> {
+        self.complete(CharsetSentence::new(), goal, C::default())
+    }
+
+    /// Find every way of adding words to `seed` so that they share no letters, satisfy `goal` and use all of
+    /// the `required` letters, modulo anagram.
+    pub fn complete<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.split(seed, goal, required).complete()
+    }
+
+    /// Split up the search [`Self::complete`] does, with a branch for each word it could start with.
+    pub(crate) fn split<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+    ) -> Split<'_, N, C> {
+        let mut sols = vec![];
+        if is_sol(&seed, goal, required) {
+            sols.push(seed);
+        }
+        if seed.len() >= N {
+            return Split {
+                sols,
+                branches: vec![],
+            };
+        }
+
+        let cands = Arc::new(self.candidates(seed));
+        let branches = set_bits(&cands, 0)
+            .map(|i| {
+                let cands = Arc::clone(&cands);
+                branch(move |mut emit| {
+                    self.complete_from(seed, i, &cands, goal, required, &mut emit)
+                })
+            })
+            .collect();
+        Split { sols, branches }
+    }
+
+    /// The words which could be added to `seed`, as a row of bits.
+    fn candidates<const N: usize>(&self, seed: CharsetSentence<N, C>) -> Vec<u64> {
+        let mut cands = vec![0; self.blocks];
+        for (j, &c) in self.words.iter().enumerate() {
+            if self.sharing.allows(seed.words(), c) {
+                cands[j / BLOCK_BITS] |= 1 << (j % BLOCK_BITS);
+            }
+        }
+        cands
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.rows[i * self.blocks..(i + 1) * self.blocks]
+    }
+
+    /// The `cands` (whose first block is block `base` of a row) which can go with word `i` and come after it,
+    /// along with the block that they start from.
+    fn narrow(&self, cands: &[u64], base: usize, i: usize) -> (Vec<u64>, usize) {
+        let start = i / BLOCK_BITS;
+        let mut next: Vec<u64> = cands[start - base..]
+            .iter()
+            .zip(&self.row(i)[start..])
+            .map(|(a, b)| a & b)
+            .collect();
+        next[0] &= u64::MAX.checked_shl(i as u32 % u64::BITS + 1).unwrap_or(0);
+        (next, start)
+    }
+
+    /// Find the solutions which extend `seed` with word `i` and then words from `cands`.
+    fn complete_from<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        i: usize,
+        cands: &[u64],
+        goal: Goal,
+        required: C,
+        emit: &mut impl FnMut(CharsetSentence<N, C>),
+    ) {
+        let init = seed.add(self.words[i]);
+        if is_sol(&init, goal, required) {
+            emit(init);
+        }
+        if init.len() < N {
+            let (next, base) = self.narrow(cands, 0, i);
+            self.find_sols(emit, init, &next, base, goal, required);
+        }
+    }
+
+    fn find_sols<const N: usize>(
+        &self,
+        emit: &mut impl FnMut(CharsetSentence<N, C>),
+        cur_sol: CharsetSentence<N, C>,
+        cands: &[u64],
+        base: usize,
+        goal: Goal,
+        required: C,
+    ) {
+        // With a fixed number of words, there have to be enough candidates left to make it up.
+        let left: u32 = cands.iter().map(|b| b.count_ones()).sum();
+        let needed = match goal {
+            Goal::Words => N - cur_sol.len(),
+            Goal::Letters(_) => 1,
+        };
+        if (left as usize) < needed {
+            return;
+        }
+
+        // Rows only know about pairs of words, so a limit on the total has to be checked as words are added.
+        let check_total = matches!(self.sharing, Sharing::Total(k) if k > 0);
+        for i in set_bits(cands, base) {
+            let c = self.words[i];
+            if check_total && !self.sharing.allows(cur_sol.words(), c) {
+                continue;
+            }
+            let sol = cur_sol.add(c);
+            if is_sol(&sol, goal, required) {
+                emit(sol);
+            }
+            if sol.len() < N {
+                let (next, next_base) = self.narrow(cands, base, i);
+                self.find_sols(emit, sol, &next, next_base, goal, required);
+            }
+        }
+    }
+}
+
+/// The indices of the bits set in `blocks`, whose first block is block `base` of a row.
+fn set_bits(blocks: &[u64], base: usize) -> impl Iterator<Item = usize> + '_ {
+    blocks.iter().enumerate().flat_map(move |(b, &block)| {
+        let mut bits = block;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let j = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some((base + b) * BLOCK_BITS + j)
+        })
+    })
+}
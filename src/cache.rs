@@ -0,0 +1,418 @@
+//! A binary cache of the anagram classes and word graph built from the word lists, so that repeated runs over
+//! the same inputs can skip reading the lists and building the graph (which takes time quadratic in the number
+//! of anagram classes).
+
+use memmap2::Mmap;
+use std::{
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    alphabet::Alphabet,
+    charset::{Charset, WideCharset},
+    dictionary::Dictionary,
+    graph::WordGraph,
+    word::Word,
+};
+
+const MAGIC: &[u8; 8] = b"5clique\0";
+
+/// Bumped whenever the format changes, so that older cache files are ignored rather than misread.
+const VERSION: u32 = 2;
+
+/// The anagram classes from the word lists, and the graph of them unless it wasn't needed.
+pub type Cached<C> = (Dictionary<C>, Option<WordGraph<C>>);
+
+/// A directory of cached dictionaries and graphs, each stored under a key made with [`CacheKey`].
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{key:016x}.graph"))
+    }
+
+    /// The dictionary and graph stored under `key`, or `None` if there aren't any (or they were stored by a
+    /// different version of the format, or the file is truncated or corrupt, so that storing them again
+    /// replaces it). `alphabet` must be the one they were built with.
+    pub fn load<C: Charset>(&self, key: u64, alphabet: &Alphabet) -> io::Result<Option<Cached<C>>> {
+        let path = self.path(key);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // Safety: cache files are only ever replaced by renaming a new file over them, never modified in place.
+        let map = unsafe { Mmap::map(&file)? };
+        match Self::decode(key, alphabet, &mut Decoder { bytes: &map }) {
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(None),
+            result => result,
+        }
+    }
+
+    fn decode<C: Charset>(
+        key: u64,
+        alphabet: &Alphabet,
+        dec: &mut Decoder,
+    ) -> io::Result<Option<Cached<C>>> {
+        let header = dec.take(MAGIC.len())? == MAGIC
+            && dec.u32()? == VERSION
+            && dec.u8()? as usize == C::CAPACITY
+            && dec.u64()? == key;
+        if !header {
+            return Ok(None);
+        }
+        // A file that was damaged after it was written can still decode to something, just not what was stored.
+        let checksum = dec.u64()?;
+        if checksum != fnv1a(dec.bytes) {
+            return Err(corrupt());
+        }
+        let dict = Dictionary::decode(alphabet.clone(), dec)?;
+        let graph = match dec.u8()? {
+            0 => None,
+            _ => Some(WordGraph::decode(dec)?),
+        };
+        Ok(Some((dict, graph)))
+    }
+
+    /// Store `dict` and `graph` under `key`, replacing anything that was there.
+    pub fn store<C: Charset>(
+        &self,
+        key: u64,
+        dict: &Dictionary<C>,
+        graph: Option<&WordGraph<C>>,
+    ) -> io::Result<()> {
+        let mut body = Encoder::default();
+        dict.encode(&mut body);
+        match graph {
+            Some(graph) => {
+                body.u8(1);
+                graph.encode(&mut body);
+            }
+            None => body.u8(0),
+        }
+
+        let mut enc = Encoder::default();
+        enc.bytes.extend_from_slice(MAGIC);
+        enc.u32(VERSION);
+        enc.u8(C::CAPACITY as u8);
+        enc.u64(key);
+        enc.u64(fnv1a(&body.bytes));
+
+        // Write to a temporary file first so that other runs never see half of it.
+        fs::create_dir_all(&self.dir)?;
+        let mut tmp = tempfile::NamedTempFile::new_in(&self.dir)?;
+        tmp.write_all(&enc.bytes)?;
+        tmp.write_all(&body.bytes)?;
+        // Temporary files are only readable by their owner, but the cache is as shareable as the word lists.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tmp.as_file()
+                .set_permissions(fs::Permissions::from_mode(0o644))?;
+        }
+        tmp.persist(self.path(key)).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+/// A hash of everything that goes into building a dictionary and graph, to store them under in a [`Cache`].
+///
+/// Keys are hashed with [`Fnv1a`] rather than the standard library's hasher, whose algorithm can change from
+/// one release of Rust to the next, so that a rebuilt binary still finds the files stored by the old one.
+pub struct CacheKey(Fnv1a);
+
+impl CacheKey {
+    pub fn new() -> Self {
+        let mut hasher = Fnv1a::default();
+        VERSION.hash(&mut hasher);
+        Self(hasher)
+    }
+
+    pub fn add(&mut self, value: impl Hash) -> &mut Self {
+        value.hash(&mut self.0);
+        self
+    }
+
+    /// Add the contents of the file at `path`, so that the key changes whenever the file does.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let file = File::open(path)?;
+        // Safety: the file is only read while hashing it, and a change part way through can only spoil the key.
+        let map = unsafe { Mmap::map(&file)? };
+        self.0.write_usize(map.len());
+        self.0.write(&map);
+        Ok(self)
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Default for CacheKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The 64-bit FNV-1a hash (see <http://www.isthe.com/chongo/tech/comp/fnv/>), which is fixed for good.
+///
+/// Integers are hashed as their little-endian bytes, and `usize`s as `u64`s, so that keys are the same on
+/// every platform too.
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Builds up the bytes of a cache file.
+#[derive(Default)]
+pub(crate) struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn u8(&mut self, n: u8) {
+        self.bytes.push(n);
+    }
+
+    pub(crate) fn u32(&mut self, n: u32) {
+        self.bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, n: u64) {
+        self.bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    pub(crate) fn count(&mut self, count: usize) {
+        self.u64(count as u64);
+    }
+
+    /// Only as many bytes as the charset needs, which halves the size of graphs for small alphabets.
+    pub(crate) fn charset<C: Charset>(&mut self, c: C) {
+        let bytes = c.to_wide().bits().to_le_bytes();
+        self.bytes.extend_from_slice(&bytes[..charset_width::<C>()]);
+    }
+
+    pub(crate) fn word(&mut self, w: Word) {
+        self.u8(w.len() as u8);
+        self.bytes.extend_from_slice(w.letters());
+    }
+}
+
+/// Reads back the bytes written by an [`Encoder`].
+pub(crate) struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if n > self.bytes.len() {
+            return Err(corrupt());
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn count(&mut self) -> io::Result<usize> {
+        usize::try_from(self.u64()?).map_err(|_| corrupt())
+    }
+
+    pub(crate) fn charset<C: Charset>(&mut self) -> io::Result<C> {
+        let width = charset_width::<C>();
+        let mut bytes = [0; 8];
+        bytes[..width].copy_from_slice(self.take(width)?);
+        let wide = WideCharset::from_bits(u64::from_le_bytes(bytes));
+        Ok(C::from_wide(wide))
+    }
+
+    pub(crate) fn word(&mut self) -> io::Result<Word> {
+        let len = self.u8()? as usize;
+        Word::from_letters(self.take(len)?).ok_or_else(corrupt)
+    }
+}
+
+/// The number of bytes a charset takes up in a cache file.
+fn charset_width<C: Charset>() -> usize {
+    C::CAPACITY.div_ceil(8)
+}
+
+fn corrupt() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "the cache file is corrupt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{charset::LowerAsciiCharset, graph::Sharing, word};
+
+    fn dictionary() -> Dictionary {
+        let words = [
+            "fjord", "waltz", "gucks", "vibex", "nymph", "chunk", "stead", "dates",
+        ];
+        Dictionary::from_words(words.into_iter().filter_map(word::parse))
+    }
+
+    fn assert_same(
+        (dict, graph): &Cached<LowerAsciiCharset>,
+        (other_dict, other_graph): &Cached<LowerAsciiCharset>,
+    ) {
+        let mut charsets: Vec<_> = dict.representatives().collect();
+        let mut other_charsets: Vec<_> = other_dict.representatives().collect();
+        charsets.sort_unstable();
+        other_charsets.sort_unstable();
+        assert_eq!(charsets, other_charsets);
+        for &c in &charsets {
+            assert_eq!(dict.anagrams(c), other_dict.anagrams(c));
+        }
+
+        assert_eq!(graph.is_some(), other_graph.is_some());
+        if let (Some(graph), Some(other_graph)) = (graph, other_graph) {
+            assert_eq!(graph.sharing(), other_graph.sharing());
+            for &c in &charsets {
+                assert_eq!(graph.neighbours(c), other_graph.neighbours(c));
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let dict = dictionary();
+        let words = dict.representatives().collect();
+        for graph in [
+            None,
+            Some(WordGraph::with_sharing(words, Sharing::Total(2))),
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            let cache = Cache::new(dir.path());
+            cache.store(7, &dict, graph.as_ref()).unwrap();
+            let loaded = cache.load(7, &Alphabet::english()).unwrap().unwrap();
+            assert_same(&(dictionary(), graph), &loaded);
+        }
+    }
+
+    #[test]
+    fn truncated_or_corrupt_files_are_rebuilt() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path());
+        let dict = dictionary();
+        let graph = dict.build_graph();
+        cache.store(7, &dict, Some(&graph)).unwrap();
+        let path = cache.path(7);
+        let bytes = fs::read(&path).unwrap();
+
+        let mut damaged: Vec<Vec<u8>> = (0..bytes.len()).map(|len| bytes[..len].to_vec()).collect();
+        for i in 0..bytes.len() {
+            let mut flipped = bytes.clone();
+            flipped[i] ^= 0x10;
+            damaged.push(flipped);
+        }
+        for damaged in damaged {
+            fs::write(&path, &damaged).unwrap();
+            let loaded = cache.load::<LowerAsciiCharset>(7, &Alphabet::english());
+            assert!(loaded.unwrap().is_none(), "loaded a damaged file");
+        }
+
+        cache.store(7, &dict, Some(&graph)).unwrap();
+        let loaded = cache.load(7, &Alphabet::english()).unwrap().unwrap();
+        assert_same(&(dict, Some(graph)), &loaded);
+    }
+
+    #[test]
+    fn other_versions_and_keys_are_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path());
+        cache.store(7, &dictionary(), None).unwrap();
+        let bytes = fs::read(cache.path(7)).unwrap();
+
+        // Stored under a different key.
+        fs::write(cache.path(8), &bytes).unwrap();
+        let loaded = cache.load::<LowerAsciiCharset>(8, &Alphabet::english());
+        assert!(loaded.unwrap().is_none());
+
+        // Stored by a different version of the format.
+        let mut old = bytes.clone();
+        old[MAGIC.len()..][..4].copy_from_slice(&(VERSION - 1).to_le_bytes());
+        fs::write(cache.path(7), &old).unwrap();
+        let loaded = cache.load::<LowerAsciiCharset>(7, &Alphabet::english());
+        assert!(loaded.unwrap().is_none());
+
+        // Stored for charsets of a different size.
+        fs::write(cache.path(7), &bytes).unwrap();
+        let loaded = cache.load::<WideCharset>(7, &Alphabet::english());
+        assert!(loaded.unwrap().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_are_readable_by_everyone() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path());
+        cache.store(7, &dictionary(), None).unwrap();
+        let mode = fs::metadata(cache.path(7)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o444, 0o444);
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt::Debug, hash::Hash};
+
+use crate::{filter_vec, word::Word};
+
+/// Number of letters in the English alphabet, which is the default.
+pub const ALPHABET_LEN: usize = 26;
+
+/// The set of letters in a word or sentence, as a bitmask indexed by each letter's position in the
+/// [`crate::Alphabet`].
+///
+/// There's one implementation for each width of bitmask, so that the usual small alphabets can keep to
+/// the narrow one, which the hot loops are tuned for.
+pub trait Charset:
+    Copy + Default + Debug + Ord + Hash + Send + Sync + From<Word> + 'static
+{
+    /// The most letters an alphabet can have to fit in this charset.
+    const CAPACITY: usize;
+
+    /// The first `len` letters of the alphabet, i.e. every letter of an alphabet with `len` letters.
+    fn first(len: usize) -> Self;
+
+    fn contains(&self, letter: u8) -> bool;
+
+    /// The letters in the set, in alphabetical order.
+    fn letters(&self) -> impl Iterator<Item = u8>;
+
+    fn intersects(&self, other: Self) -> bool;
+
+    /// The number of letters in both `self` and `other`.
+    fn shared(&self, other: Self) -> u32;
+
+    fn is_superset(&self, other: Self) -> bool;
+
+    /// The letters which are in `self` but not in `other`.
+    fn difference(&self, other: Self) -> Self;
+
+    fn union(&mut self, other: Self);
+
+    fn len(&self) -> u32;
+
+    fn is_empty(&self) -> bool;
+
+    /// The letters as a 64-bit mask, which is wide enough for any charset.
+    fn to_wide(&self) -> WideCharset;
+
+    /// The letters of `wide`, which must all fit in this charset.
+    fn from_wide(wide: WideCharset) -> Self;
+
+    /// The charsets in `input` which share at most `max_shared` letters with `charset` and come after
+    /// `last_added`. This is the innermost loop of the clique search, so each width gets its own implementation.
+    fn filter_vec(input: &[Self], charset: Self, last_added: Self, max_shared: u32) -> Vec<Self>;
+}
+
+/// A [`Charset`] for alphabets of up to 31 letters, such as English. It's named for the alphabet it was
+/// written for, and the vectorised search relies on the top bit never being set.
+#[derive(Clone, Copy, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct LowerAsciiCharset(u32);
+
+/// A [`Charset`] for alphabets of up to 64 letters.
+#[derive(Clone, Copy, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct WideCharset(u64);
+
+impl WideCharset {
+    /// The letters as a bitmask, with the first letter of the alphabet in the lowest bit.
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+}
+
+macro_rules! impl_charset {
+    ($charset:ident, $bits:ty, $capacity:expr, $filter_vec:path) => {
+        impl From<Word> for $charset {
+            fn from(w: Word) -> Self {
+                let mut chars = 0;
+                w.letters().iter().for_each(|b| chars |= 1 << b);
+                Self(chars)
+            }
+        }
+
+        impl Charset for $charset {
+            const CAPACITY: usize = $capacity;
+
+            fn first(len: usize) -> Self {
+                assert!(
+                    len <= Self::CAPACITY,
+                    "too many letters for {}",
+                    stringify!($charset)
+                );
+                Self(
+                    <$bits>::MAX
+                        .checked_shr(<$bits>::BITS - len as u32)
+                        .unwrap_or(0),
+                )
+            }
+
+            fn contains(&self, letter: u8) -> bool {
+                self.0 & (1 << letter) != 0
+            }
+
+            fn letters(&self) -> impl Iterator<Item = u8> {
+                let bits = self.0;
+                (0..<$bits>::BITS as u8).filter(move |b| bits & (1 << b) != 0)
+            }
+
+            fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            fn shared(&self, other: Self) -> u32 {
+                (self.0 & other.0).count_ones()
+            }
+
+            fn is_superset(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            fn difference(&self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+
+            fn union(&mut self, other: Self) {
+                self.0 |= other.0
+            }
+
+            fn len(&self) -> u32 {
+                self.0.count_ones()
+            }
+
+            fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            fn to_wide(&self) -> WideCharset {
+                WideCharset(self.0 as u64)
+            }
+
+            fn from_wide(wide: WideCharset) -> Self {
+                Self(wide.0 as $bits)
+            }
+
+            fn filter_vec(
+                input: &[Self],
+                charset: Self,
+                last_added: Self,
+                max_shared: u32,
+            ) -> Vec<Self> {
+                $filter_vec(input, charset, last_added, max_shared)
+            }
+        }
+    };
+}
+
+impl_charset!(LowerAsciiCharset, u32, 31, filter_vec::filter_vec);
+impl_charset!(WideCharset, u64, 64, filter_vec::filter_vec_scalar);
@@ -0,0 +1,115 @@
+//! Complete sentences which already have some of their words chosen, e.g. to give hints in a game.
+
+use crate::{
+    charset::{Charset, LowerAsciiCharset},
+    constraints::{ConstraintError, Constraints},
+    dictionary::Dictionary,
+    graph::{Goal, WordGraph},
+    sentence::Sentence,
+    word::Word,
+};
+
+/// Answers completion queries against a graph which is built once up front.
+///
+/// ```no_run
+/// use five_clique::{word, Completer, Dictionary};
+///
+/// let dict = Dictionary::from_path("words_alpha.txt", 5..=5)?;
+/// let graph = dict.build_graph();
+/// let completer = Completer::new(&dict, &graph);
+///
+/// let partial = [word::parse("fjord").unwrap(), word::parse("waltz").unwrap()];
+/// for sentence in completer.complete::<5>(&partial)? {
+///     println!("{}", sentence.as_string());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Completer<'a, C = LowerAsciiCharset> {
+    dict: &'a Dictionary<C>,
+    graph: &'a WordGraph<C>,
+}
+
+impl<'a, C: Charset> Completer<'a, C> {
+    /// `graph` should have been built from `dict`, e.g. with [`Dictionary::build_graph`].
+    pub fn new(dict: &'a Dictionary<C>, graph: &'a WordGraph<C>) -> Self {
+        Self { dict, graph }
+    }
+
+    /// Find every sentence of `N` words that share no more letters than the graph allows (see
+    /// [`WordGraph::sharing`]) and contain all of `words`.
+    ///
+    /// Fails if one of `words` isn't in the dictionary, some of them share too many letters, or there are more
+    /// than `N` of them.
+    pub fn complete<const N: usize>(
+        &self,
+        words: &[Word],
+    ) -> Result<Vec<Sentence<N>>, ConstraintError> {
+        let constraints = Constraints::<C> {
+            include: words.to_vec(),
+            sharing: self.graph.sharing(),
+            ..Default::default()
+        };
+        let seed = constraints.seed::<N>(self.dict)?;
+
+        let candidates = self.graph.common_neighbours(seed.words());
+        let sols = self
+            .graph
+            .complete_among(seed, &candidates, Goal::Words, C::default());
+
+        // The chosen words stand for their whole anagram class until now.
+        let mut sentences = self.dict.expand_all(sols);
+        sentences.retain(|s| words.iter().all(|w| s.words().any(|x| x == *w)));
+        Ok(sentences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::Sharing, word};
+    use itertools::Itertools;
+    use std::fs;
+
+    /// Every `step`th word of `words_five.txt`, plus `fjord`, `waltz` and `pouch`.
+    fn dictionary(step: usize) -> Dictionary {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/words_five.txt");
+        let list = fs::read_to_string(path).unwrap();
+        let words = list
+            .lines()
+            .step_by(step)
+            .filter(|w| !["fjord", "waltz", "pouch"].contains(w))
+            .chain(["fjord", "waltz", "pouch"]);
+        Dictionary::from_words(words.filter_map(word::parse))
+    }
+
+    /// The sentences as strings with their words in order, so that they compare as sets of words.
+    fn sorted<const N: usize>(sentences: &[Sentence<N>]) -> Vec<String> {
+        let mut sorted: Vec<_> = sentences
+            .iter()
+            .map(|s| s.as_string().split(' ').sorted().join(" "))
+            .collect();
+        sorted.sort_unstable();
+        sorted
+    }
+
+    fn check<const N: usize>(dict: &Dictionary, sharing: Sharing, partial: [&str; 2]) {
+        let partial = partial.map(|w| word::parse(w).unwrap());
+        let graph = WordGraph::with_sharing(dict.representatives().collect(), sharing);
+        let completed = Completer::new(dict, &graph)
+            .complete::<N>(&partial)
+            .unwrap();
+
+        let mut expected = dict.expand_all(graph.search::<N>(Goal::Words));
+        expected.retain(|s| partial.iter().all(|w| s.words().any(|x| x == *w)));
+        assert!(!expected.is_empty());
+        assert_eq!(sorted(&completed), sorted(&expected), "{sharing:?}");
+    }
+
+    #[test]
+    fn completions_are_the_solutions_with_the_words() {
+        check::<4>(&dictionary(4), Sharing::default(), ["fjord", "waltz"]);
+        // Sharing letters allows many more solutions, so fewer words will do. The words given can share too.
+        check::<3>(&dictionary(40), Sharing::Pairwise(1), ["fjord", "pouch"]);
+        check::<3>(&dictionary(40), Sharing::Total(2), ["fjord", "pouch"]);
+    }
+}
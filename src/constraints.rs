@@ -0,0 +1,103 @@
+//! Restrictions on which words a solution may contain.
+
+use rustc_hash::FxHashSet;
+use std::{error::Error, fmt};
+
+use crate::{
+    charset::{Charset, LowerAsciiCharset},
+    dictionary::Dictionary,
+    graph::Sharing,
+    sentence::CharsetSentence,
+    word::Word,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct Constraints<C = LowerAsciiCharset> {
+    /// Words every solution must contain.
+    pub include: Vec<Word>,
+    /// Words no solution may contain.
+    pub exclude: FxHashSet<Word>,
+    /// Letters every solution must use.
+    pub require_letters: C,
+    /// Letters no solution may use.
+    pub forbid_letters: C,
+    /// How many letters the words of a solution may share, including the included ones.
+    pub sharing: Sharing,
+}
+
+impl<C: Charset> Constraints<C> {
+    /// Whether a solution could contain `w`.
+    ///
+    /// The other anagrams of an included word are ruled out, so that solutions contain exactly the word that
+    /// was asked for rather than its whole anagram class.
+    pub fn allows(&self, w: Word) -> bool {
+        let charset = C::from(w);
+        !charset.intersects(self.forbid_letters)
+            && !self.exclude.contains(&w)
+            && self
+                .include
+                .iter()
+                .all(|&i| i == w || C::from(i) != charset)
+    }
+
+    /// The sentence made of just the included words, which every solution extends.
+    ///
+    /// `dict` should already have been restricted with [`Dictionary::retain`] and [`Self::allows`].
+    pub fn seed<const N: usize>(
+        &self,
+        dict: &Dictionary<C>,
+    ) -> Result<CharsetSentence<N, C>, ConstraintError> {
+        if self.include.len() > N {
+            return Err(ConstraintError::TooManyWords(N));
+        }
+
+        let mut seed = CharsetSentence::new();
+        for (i, &w) in self.include.iter().enumerate() {
+            let charset = C::from(w);
+            let show = |w| dict.alphabet().show(w);
+            if !dict.anagrams(charset).contains(&w) {
+                return Err(ConstraintError::UnknownWord(show(w)));
+            }
+            if !self.sharing.allows(seed.words(), charset) {
+                // Name a word it shares too much with, or failing that (if it's only too much in total) any
+                // word it shares with.
+                let earlier = &self.include[..i];
+                let other = earlier
+                    .iter()
+                    .find(|&&o| !self.sharing.allows(&[C::from(o)], charset))
+                    .or_else(|| earlier.iter().find(|&&o| C::from(o).intersects(charset)))
+                    .map_or_else(String::new, |&o| show(o));
+                return Err(ConstraintError::Overlap(other, show(w)));
+            }
+            seed = seed.add(charset);
+        }
+        Ok(seed)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintError {
+    /// An included word isn't in the dictionary, or is ruled out by the other constraints.
+    UnknownWord(String),
+    /// Two included words share a letter, or more letters than are allowed.
+    Overlap(String, String),
+    /// More words were included than fit in a sentence.
+    TooManyWords(usize),
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::UnknownWord(w) => write!(
+                f,
+                "`{w}` isn't in the dictionary, or is ruled out by the other constraints"
+            ),
+            ConstraintError::Overlap(a, b) => write!(f, "`{a}` and `{b}` share too many letters"),
+            ConstraintError::TooManyWords(n) => {
+                write!(f, "can't include more than {n} words in a solution")
+            }
+        }
+    }
+}
+
+impl Error for ConstraintError {}
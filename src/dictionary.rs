@@ -0,0 +1,202 @@
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+use std::{
+    io::{self, BufRead},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use crate::{
+    alphabet::Alphabet,
+    cache::{Decoder, Encoder},
+    charset::{Charset, LowerAsciiCharset},
+    graph::WordGraph,
+    input,
+    sentence::{CharsetSentence, Sentence},
+    word::{self, distinct_letters, word_chars_sorted, Word},
+};
+
+/// The words from a word list which have an accepted length and no repeated letters, grouped into anagram
+/// classes.
+pub struct Dictionary<C = LowerAsciiCharset> {
+    alphabet: Alphabet,
+    anagrams: FxHashMap<C, Vec<Word>>,
+}
+
+impl Dictionary {
+    /// Read the words with a length in `word_lens` from a word list with one word per line, which may be
+    /// compressed or `-` for stdin (see [`input::open`]).
+    pub fn from_path(path: impl AsRef<Path>, word_lens: RangeInclusive<usize>) -> io::Result<Self> {
+        Self::from_reader(input::open(path)?, word_lens)
+    }
+
+    /// Read the words with a length in `word_lens` from a word list with one word per line.
+    pub fn from_reader(reader: impl BufRead, word_lens: RangeInclusive<usize>) -> io::Result<Self> {
+        let mut words = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if word_lens.contains(&line.len()) {
+                words.extend(word::parse(&line));
+            }
+        }
+        Ok(Self::from_words(words))
+    }
+
+    /// Group English `words` into anagram classes.
+    pub fn from_words(words: impl IntoIterator<Item = Word>) -> Self {
+        Self::from_words_in(Alphabet::english(), words)
+    }
+}
+
+impl<C: Charset> Dictionary<C> {
+    /// Group `words`, which are made of letters from `alphabet`, into anagram classes.
+    pub fn from_words_in(alphabet: Alphabet, words: impl IntoIterator<Item = Word>) -> Self {
+        assert!(
+            alphabet.len() <= C::CAPACITY,
+            "too many letters for the charset"
+        );
+        let anagrams = words
+            .into_iter()
+            .filter(|w| distinct_letters(*w))
+            .sorted_unstable_by_key(|w| word_chars_sorted(*w))
+            .group_by(|w| word_chars_sorted(*w))
+            .into_iter()
+            .map(|(_, group)| {
+                let group = group.collect_vec();
+                (C::from(group[0]), group)
+            })
+            .collect();
+        Self { alphabet, anagrams }
+    }
+
+    /// The alphabet the words are made of.
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    /// The number of anagram classes.
+    pub fn len(&self) -> usize {
+        self.anagrams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anagrams.is_empty()
+    }
+
+    /// Only keep the words for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(Word) -> bool) {
+        self.anagrams.retain(|_, group| {
+            group.retain(|w| f(*w));
+            !group.is_empty()
+        });
+    }
+
+    /// One charset for each anagram class.
+    pub fn representatives(&self) -> impl Iterator<Item = C> + '_ {
+        self.anagrams.keys().copied()
+    }
+
+    /// All the words with exactly the letters in `charset`.
+    pub fn anagrams(&self, charset: C) -> &[Word] {
+        self.anagrams.get(&charset).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn encode(&self, enc: &mut Encoder) {
+        enc.count(self.anagrams.len());
+        for (&charset, group) in &self.anagrams {
+            enc.charset(charset);
+            enc.count(group.len());
+            group.iter().for_each(|&w| enc.word(w));
+        }
+    }
+
+    pub(crate) fn decode(alphabet: Alphabet, dec: &mut Decoder) -> io::Result<Self> {
+        let mut anagrams = FxHashMap::default();
+        for _ in 0..dec.count()? {
+            let charset = dec.charset()?;
+            let group = (0..dec.count()?)
+                .map(|_| dec.word())
+                .collect::<io::Result<_>>()?;
+            anagrams.insert(charset, group);
+        }
+        Ok(Self { alphabet, anagrams })
+    }
+
+    pub fn build_graph(&self) -> WordGraph<C> {
+        WordGraph::new(self.representatives().collect())
+    }
+
+    /// Push every sentence that `sol` represents, i.e. every choice of anagram for each of its words.
+    pub fn expand_anagrams<const N: usize>(
+        &self,
+        sols: &mut Vec<Sentence<N>>,
+        sol: CharsetSentence<N, C>,
+    ) {
+        self.for_each_anagram(sol, |sentence| sols.push(sentence));
+    }
+
+    /// Call `f` with every sentence that `sol` represents.
+    pub fn for_each_anagram<const N: usize>(
+        &self,
+        sol: CharsetSentence<N, C>,
+        mut f: impl FnMut(Sentence<N>),
+    ) {
+        let mut a_idxs = vec![0; sol.len()];
+        let agrams = sol.words().iter().map(|w| self.anagrams(*w)).collect_vec();
+
+        loop {
+            /*
+            The idea is to maintain a vec of indexes into the anagram vector, so that a_idx[i] is the index into agrams[i].
+            We want to find all permutations of anagrams, each of which corresponds to a value for a_idx, so we just count
+            through all the possible values for a_idx by 'ticking' the first index, and let it overflow (or carry-over)
+            into the next indexes when necessary. We stop when the last index overflows.
+
+            We could also do it with five nested for-loops, but where's the fun in that? Also, it wouldn't generalise.
+            */
+            let sentence: Sentence<N> = a_idxs
+                .iter()
+                .enumerate()
+                .map(|(i, idx)| agrams[i][*idx])
+                .sorted()
+                .into();
+
+            f(sentence);
+
+            let mut i = 0;
+            a_idxs[0] += 1;
+            while a_idxs[i] >= agrams[i].len() {
+                a_idxs[i] = 0;
+                i += 1;
+                if i >= a_idxs.len() {
+                    return;
+                }
+                a_idxs[i] += 1;
+            }
+        }
+    }
+
+    /// The anagram class of each word of `sol`, in the same order as its words.
+    pub fn anagram_groups<const N: usize>(&self, sol: &CharsetSentence<N, C>) -> Vec<&[Word]> {
+        sol.words().iter().map(|w| self.anagrams(*w)).collect()
+    }
+
+    /// The number of sentences that `sol` represents, without expanding them.
+    pub fn count_anagrams<const N: usize>(&self, sol: &CharsetSentence<N, C>) -> usize {
+        sol.words()
+            .iter()
+            .map(|w| self.anagrams(*w).len())
+            .product()
+    }
+
+    /// Expand every solution into all of the sentences it represents.
+    pub fn expand_all<const N: usize>(
+        &self,
+        sols: impl IntoIterator<Item = CharsetSentence<N, C>>,
+    ) -> Vec<Sentence<N>> {
+        let mut out = vec![];
+        for sol in sols {
+            self.expand_anagrams(&mut out, sol);
+        }
+        out
+    }
+}
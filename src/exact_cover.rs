@@ -0,0 +1,215 @@
+//! Find sets of words which use every letter of the alphabet exactly once.
+//!
+//! This is an exact cover problem (the letters are the items and the words are the options), so instead of
+//! the clique search in [`crate::graph`] it uses Knuth's Algorithm X: always branch on the uncovered letter
+//! with the fewest remaining candidate words. Every word must cover a letter that would otherwise be left
+//! over, so this prunes far harder than growing cliques in charset order.
+
+use rayon::prelude::*;
+use std::{ops::ControlFlow, sync::Arc};
+
+use crate::{
+    charset::{Charset, LowerAsciiCharset, WideCharset, ALPHABET_LEN},
+    graph::Count,
+    sentence::CharsetSentence,
+    solver::{branch, Split},
+};
+
+pub struct ExactCover<C = LowerAsciiCharset> {
+    /// The letters every solution has to use.
+    letters: C,
+    words: Vec<C>,
+}
+
+impl ExactCover {
+    /// Cover the English alphabet with `words`.
+    pub fn new(words: impl IntoIterator<Item = LowerAsciiCharset>) -> Self {
+        Self::with_letters(LowerAsciiCharset::first(ALPHABET_LEN), words)
+    }
+}
+
+impl<C: Charset> ExactCover<C> {
+    /// Cover exactly `letters` with `words`, e.g. every letter of some other [`crate::Alphabet`].
+    pub fn with_letters(letters: C, words: impl IntoIterator<Item = C>) -> Self {
+        let mut words: Vec<_> = words
+            .into_iter()
+            .filter(|c| !c.is_empty() && letters.is_superset(*c))
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+        Self { letters, words }
+    }
+
+    /// Find every set of at most `N` words whose charsets partition the alphabet, modulo anagram.
+    pub fn solve<const N: usize>(&self) -> Vec<CharsetSentence<N, C>> {
+        self.complete(CharsetSentence::new())
+    }
+
+    /// Find every way of adding words to `seed` so that the charsets partition the alphabet, modulo anagram.
+    pub fn complete<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.split(seed).complete()
+    }
+
+    /// Like [`Self::complete`], but calls `f` with each solution as soon as it's found instead of collecting
+    /// them. `f` is called from many threads at once, and the search stops early once it returns
+    /// [`ControlFlow::Break`].
+    pub fn complete_each<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        f: impl Fn(CharsetSentence<N, C>) -> ControlFlow<()> + Sync,
+    ) -> ControlFlow<()> {
+        self.split(seed).each(f)
+    }
+
+    /// Split up the search [`Self::complete`] does, with a branch for each word using the letter with the
+    /// fewest.
+    pub(crate) fn split<const N: usize>(&self, seed: CharsetSentence<N, C>) -> Split<'_, N, C> {
+        let Some((letter, candidates)) = self.branches(seed) else {
+            return Split {
+                sols: (seed.charset() == self.letters)
+                    .then_some(seed)
+                    .into_iter()
+                    .collect(),
+                branches: vec![],
+            };
+        };
+
+        let candidates = Arc::new(candidates);
+        let branches = candidates
+            .iter()
+            .filter(|c| c.contains(letter))
+            .map(|&c| {
+                let candidates = Arc::clone(&candidates);
+                branch(move |mut emit| {
+                    let cur_sol = seed.add(c);
+                    let candidates = compatible(&candidates, cur_sol.charset());
+                    solve(&mut emit, self.letters, cur_sol, &candidates);
+                })
+            })
+            .collect();
+        Split {
+            sols: vec![],
+            branches,
+        }
+    }
+
+    /// Count the solutions [`Self::complete`] would find without collecting them, weighting each one as in
+    /// [`crate::WordGraph::count`].
+    pub fn count<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        weight: impl Fn(C) -> u64 + Sync,
+    ) -> Count {
+        let seed_weight = seed.words().iter().map(|w| weight(*w)).product();
+        let Some((letter, candidates)) = self.branches(seed) else {
+            let mut count = Count::default();
+            if seed.charset() == self.letters {
+                count.add_sol(seed_weight);
+            }
+            return count;
+        };
+
+        candidates
+            .par_iter()
+            .filter(|c| c.contains(letter))
+            .map(|&c| {
+                let mut count = Count::default();
+                let cur_sol = seed.add(c);
+                let cur = (cur_sol, seed_weight * weight(c));
+                let candidates = compatible(&candidates, cur_sol.charset());
+                count_sols(&mut count, self.letters, cur, &candidates, &weight);
+                count
+            })
+            .sum()
+    }
+
+    /// The letter to branch on first and the words which could be added to `seed`, or `None` if there's
+    /// nothing to search.
+    fn branches<const N: usize>(&self, seed: CharsetSentence<N, C>) -> Option<(u8, Vec<C>)> {
+        if seed.charset() == self.letters || seed.len() >= N {
+            return None;
+        }
+        let candidates = compatible(&self.words, seed.charset());
+        let letter = rarest_letter(&candidates, self.letters.difference(seed.charset()))?;
+        Some((letter, candidates))
+    }
+}
+
+fn solve<const N: usize, C: Charset>(
+    emit: &mut impl FnMut(CharsetSentence<N, C>),
+    letters: C,
+    cur_sol: CharsetSentence<N, C>,
+    candidates: &[C],
+) {
+    let uncovered = letters.difference(cur_sol.charset());
+    if uncovered.is_empty() {
+        emit(cur_sol);
+        return;
+    }
+    if cur_sol.len() >= N {
+        return;
+    }
+
+    // Some word has to cover this letter, so it's enough to branch on the words which do.
+    let Some(letter) = rarest_letter(candidates, uncovered) else {
+        return;
+    };
+    for &c in candidates.iter().filter(|c| c.contains(letter)) {
+        let sol = cur_sol.add(c);
+        solve(emit, letters, sol, &compatible(candidates, sol.charset()));
+    }
+}
+
+/// Like [`solve`], but only counts the solutions, and `cur_sol` comes with the weight of its words so far.
+fn count_sols<const N: usize, C: Charset>(
+    count: &mut Count,
+    letters: C,
+    (cur_sol, cur_weight): (CharsetSentence<N, C>, u64),
+    candidates: &[C],
+    weight: &impl Fn(C) -> u64,
+) {
+    let uncovered = letters.difference(cur_sol.charset());
+    if uncovered.is_empty() {
+        count.add_sol(cur_weight);
+        return;
+    }
+    if cur_sol.len() >= N {
+        return;
+    }
+
+    let Some(letter) = rarest_letter(candidates, uncovered) else {
+        return;
+    };
+    for &c in candidates.iter().filter(|c| c.contains(letter)) {
+        let sol = cur_sol.add(c);
+        let next = (sol, cur_weight * weight(c));
+        let candidates = compatible(candidates, sol.charset());
+        count_sols(count, letters, next, &candidates, weight);
+    }
+}
+
+/// The candidates which don't share any letters with `charset`.
+fn compatible<C: Charset>(candidates: &[C], charset: C) -> Vec<C> {
+    candidates
+        .iter()
+        .copied()
+        .filter(|c| !c.intersects(charset))
+        .collect()
+}
+
+/// The letter of `uncovered` which is in the fewest candidates, or `None` if some letter can't be covered
+/// at all (in which case there are no solutions).
+fn rarest_letter<C: Charset>(candidates: &[C], uncovered: C) -> Option<u8> {
+    let mut counts = [0usize; WideCharset::CAPACITY];
+    for c in candidates {
+        c.letters().for_each(|l| counts[l as usize] += 1);
+    }
+
+    uncovered
+        .letters()
+        .min_by_key(|&l| counts[l as usize])
+        .filter(|&l| counts[l as usize] > 0)
+}
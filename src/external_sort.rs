@@ -0,0 +1,152 @@
+//! Sort more lines than fit in memory, by spilling sorted runs to temporary files and merging them at the end.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Lines, Seek, SeekFrom, Write},
+    mem,
+};
+
+pub struct ExternalSorter {
+    buf: Vec<String>,
+    buf_bytes: usize,
+    max_bytes: usize,
+    runs: Vec<File>,
+}
+
+impl ExternalSorter {
+    /// Keep roughly `max_bytes` of lines in memory before spilling them to disk.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            buf: vec![],
+            buf_bytes: 0,
+            max_bytes,
+            runs: vec![],
+        }
+    }
+
+    /// Add a line, which shouldn't contain any newlines.
+    pub fn push(&mut self, line: String) -> io::Result<()> {
+        self.buf_bytes += line.len() + mem::size_of::<String>();
+        self.buf.push(line);
+        if self.buf_bytes >= self.max_bytes {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.buf.sort_unstable();
+        let mut f = BufWriter::new(tempfile::tempfile()?);
+        for line in self.buf.drain(..) {
+            writeln!(f, "{line}")?;
+        }
+        let mut f = f.into_inner().map_err(|e| e.into_error())?;
+        f.seek(SeekFrom::Start(0))?;
+
+        self.runs.push(f);
+        self.buf_bytes = 0;
+        Ok(())
+    }
+
+    /// Write all of the lines to `out` in sorted order.
+    pub fn finish(self, out: &mut (impl Write + ?Sized)) -> io::Result<()> {
+        self.finish_with(out, |line| line)
+    }
+
+    /// Like [`Self::finish`], but writing `f(line)` for each line, e.g. to strip off a prefix which was only
+    /// there to sort by.
+    pub fn finish_with(
+        mut self,
+        out: &mut (impl Write + ?Sized),
+        f: impl Fn(&str) -> &str,
+    ) -> io::Result<()> {
+        if self.runs.is_empty() {
+            self.buf.sort_unstable();
+            for line in &self.buf {
+                writeln!(out, "{}", f(line))?;
+            }
+            return Ok(());
+        }
+
+        self.spill()?;
+        let mut runs: Vec<Lines<BufReader<File>>> = self
+            .runs
+            .into_iter()
+            .map(|f| BufReader::new(f).lines())
+            .collect();
+
+        // Repeatedly take the smallest of the first lines of the runs.
+        let mut heap = BinaryHeap::new();
+        for (i, run) in runs.iter_mut().enumerate() {
+            if let Some(line) = run.next() {
+                heap.push(Reverse((line?, i)));
+            }
+        }
+        while let Some(Reverse((line, i))) = heap.pop() {
+            writeln!(out, "{}", f(&line))?;
+            if let Some(next) = runs[i].next() {
+                heap.push(Reverse((next?, i)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arbitrary but repeatable lines, some of them repeated.
+    fn lines(len: usize) -> Vec<String> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                format!("{:03} {}", state % 500, state % 7)
+            })
+            .collect()
+    }
+
+    fn sort(max_bytes: usize, lines: &[String]) -> (usize, String) {
+        let mut sorter = ExternalSorter::new(max_bytes);
+        for line in lines {
+            sorter.push(line.clone()).unwrap();
+        }
+        let runs = sorter.runs.len();
+        let mut out = vec![];
+        sorter.finish(&mut out).unwrap();
+        (runs, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn merges_runs_in_order() {
+        let lines = lines(1000);
+        let mut expected = lines.clone();
+        expected.sort_unstable();
+        let expected: String = expected.iter().map(|line| format!("{line}\n")).collect();
+
+        // Everything in memory, a few runs, and lots of runs with a line left over in memory.
+        for (max_bytes, min_runs) in [(1 << 20, 0), (4 << 10, 5), (1000, 25)] {
+            let (runs, out) = sort(max_bytes, &lines);
+            assert!(runs >= min_runs, "{runs} runs of {max_bytes} bytes");
+            assert_eq!(out, expected, "{max_bytes} bytes");
+        }
+    }
+
+    #[test]
+    fn finish_with_strips_keys() {
+        for max_bytes in [1 << 20, 100] {
+            let mut sorter = ExternalSorter::new(max_bytes);
+            for (key, word) in [(3, "fjord"), (1, "waltz"), (2, "vibex"), (1, "gucks")] {
+                sorter.push(format!("{key}\t{word}")).unwrap();
+            }
+            let mut out = vec![];
+            sorter.finish_with(&mut out, |line| &line[2..]).unwrap();
+            assert_eq!(out, b"gucks\nwaltz\nvibex\nfjord\n", "{max_bytes} bytes");
+        }
+    }
+}
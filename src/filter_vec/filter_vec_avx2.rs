@@ -1,5 +1,5 @@
 const NUM_LANES: usize = 8;
-use super::LowerAsciiCharset;
+use super::{Charset, LowerAsciiCharset};
 
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
@@ -42,8 +42,8 @@ unsafe fn filter_vec_avx2_aux(
     num_words: usize,
 ) -> usize {
     let mut output_tail = output;
-    let charset_simd = _mm256_set1_epi32(transmute(charset));
-    let last_added_simd = _mm256_set1_epi32(transmute(last_added));
+    let charset_simd = _mm256_set1_epi32(transmute::<LowerAsciiCharset, i32>(charset));
+    let last_added_simd = _mm256_set1_epi32(transmute::<LowerAsciiCharset, i32>(last_added));
     for _ in 0..num_words {
         let word = _mm256_loadu_si256(input);
         let keeper_bitset = compute_filter_bitset(word, charset_simd, last_added_simd);
@@ -56,6 +56,57 @@ unsafe fn filter_vec_avx2_aux(
     output_tail.offset_from(output) as usize
 }
 
+pub fn filter_vec_shared_avx2(input: &[LowerAsciiCharset], charset: LowerAsciiCharset, last_added: LowerAsciiCharset, max_shared: u32) -> Vec<LowerAsciiCharset> {
+    let mut output = Vec::with_capacity(input.len());
+    let num_words = input.len() / NUM_LANES;
+    unsafe {
+        let output_len = filter_vec_shared_avx2_aux(
+            input.as_ptr() as *const __m256i,
+            charset,
+            last_added,
+            max_shared,
+            output.as_mut_ptr(),
+            num_words,
+        );
+        output.set_len(output_len);
+    }
+
+    // don't forget the excess
+    for i in 0..(input.len() % NUM_LANES) {
+        let idx = num_words * NUM_LANES + i;
+        let c = input[idx];
+        if charset.shared(c) <= max_shared && c > last_added {
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+unsafe fn filter_vec_shared_avx2_aux(
+    mut input: *const __m256i,
+    charset: LowerAsciiCharset,
+    last_added: LowerAsciiCharset,
+    max_shared: u32,
+    output: *mut LowerAsciiCharset,
+    num_words: usize,
+) -> usize {
+    let mut output_tail = output;
+    let charset_simd = _mm256_set1_epi32(transmute::<LowerAsciiCharset, i32>(charset));
+    let last_added_simd = _mm256_set1_epi32(transmute::<LowerAsciiCharset, i32>(last_added));
+    let max_shared_simd = _mm256_set1_epi32(max_shared as i32);
+    for _ in 0..num_words {
+        let word = _mm256_loadu_si256(input);
+        let keeper_bitset = compute_shared_filter_bitset(word, charset_simd, last_added_simd, max_shared_simd);
+        let added_len = keeper_bitset.count_ones();
+        let compacted_output = compact(word, keeper_bitset);
+        _mm256_storeu_si256(output_tail as *mut __m256i, compacted_output);
+        output_tail = output_tail.offset(added_len as isize);
+        input = input.offset(1);
+    }
+    output_tail.offset_from(output) as usize
+}
+
 #[inline]
 unsafe fn compact(data: __m256i, mask: u8) -> __m256i {
     let vperm_mask = BITSET_TO_MAPPING[mask as usize];
@@ -72,6 +123,37 @@ unsafe fn compute_filter_bitset(val: __m256i, charset_simd: __m256i, last_added_
     _mm256_movemask_ps(ztest) as u8
 }
 
+/// Like `compute_filter_bitset`, but allowing up to `max_shared_simd` letters in common.
+#[inline]
+unsafe fn compute_shared_filter_bitset(val: __m256i, charset_simd: __m256i, last_added_simd: __m256i, max_shared_simd: __m256i) -> u8 {
+    let shared = popcount_epi32(_mm256_and_si256(val, charset_simd));
+    // A word can share few enough letters with itself once sharing is allowed, so unlike
+    // `compute_filter_bitset` this has to reject `last_added` itself as well as the words before it.
+    let not_after = _mm256_or_si256(
+        _mm256_cmpgt_epi32(last_added_simd, val),
+        _mm256_cmpeq_epi32(last_added_simd, val),
+    );
+    let reject: __m256i = _mm256_or_si256(_mm256_cmpgt_epi32(shared, max_shared_simd), not_after);
+    let ztest = transmute::<__m256i, __m256>(_mm256_cmpeq_epi32(reject, _mm256_set1_epi32(0)));
+    _mm256_movemask_ps(ztest) as u8
+}
+
+/// The number of set bits in each 32-bit lane. There's no popcount instruction before AVX-512, so this looks up
+/// the count for each nibble and then adds up the bytes of each lane.
+#[inline]
+unsafe fn popcount_epi32(val: __m256i) -> __m256i {
+    let nibble_counts = _mm256_setr_epi8(
+        0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+    );
+    let low_nibbles = _mm256_set1_epi8(0x0f);
+    let lo = _mm256_shuffle_epi8(nibble_counts, _mm256_and_si256(val, low_nibbles));
+    let hi = _mm256_shuffle_epi8(nibble_counts, _mm256_and_si256(_mm256_srli_epi32(val, 4), low_nibbles));
+    let byte_counts = _mm256_add_epi8(lo, hi);
+    let pair_counts = _mm256_maddubs_epi16(byte_counts, _mm256_set1_epi8(1));
+    _mm256_madd_epi16(pair_counts, _mm256_set1_epi16(1))
+}
+
 const fn from_u32x8(vals: [u32; NUM_LANES]) -> __m256i {
     union U8x32 {
         vector: __m256i,
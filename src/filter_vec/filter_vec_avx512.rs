@@ -0,0 +1,76 @@
+const NUM_LANES: usize = 16;
+use super::{Charset, LowerAsciiCharset};
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use std::mem::transmute;
+
+pub fn filter_vec_avx512(
+    input: &[LowerAsciiCharset],
+    charset: LowerAsciiCharset,
+    last_added: LowerAsciiCharset,
+) -> Vec<LowerAsciiCharset> {
+    let mut output = Vec::with_capacity(input.len());
+    let num_words = input.len() / NUM_LANES;
+    unsafe {
+        let output_len = filter_vec_avx512_aux(
+            input.as_ptr() as *const __m512i,
+            charset,
+            last_added,
+            output.as_mut_ptr(),
+            num_words,
+        );
+        output.set_len(output_len);
+    }
+
+    // don't forget the excess
+    for i in 0..(input.len() % NUM_LANES) {
+        let idx = num_words * NUM_LANES + i;
+        let c = input[idx];
+        if !charset.intersects(c) && c > last_added {
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+#[target_feature(enable = "avx512f")]
+unsafe fn filter_vec_avx512_aux(
+    mut input: *const __m512i,
+    charset: LowerAsciiCharset,
+    last_added: LowerAsciiCharset,
+    output: *mut LowerAsciiCharset,
+    num_words: usize,
+) -> usize {
+    let mut output_tail = output;
+    let charset_simd = _mm512_set1_epi32(transmute::<LowerAsciiCharset, i32>(charset));
+    let last_added_simd = _mm512_set1_epi32(transmute::<LowerAsciiCharset, i32>(last_added));
+    for _ in 0..num_words {
+        let word = _mm512_loadu_si512(input);
+        let keeper_bitset = compute_filter_bitset(word, charset_simd, last_added_simd);
+        let added_len = keeper_bitset.count_ones();
+        // Compacting in a register and storing all 16 lanes is much faster than `vpcompressd` straight to memory
+        // on some CPUs (e.g. Zen 4), and the output has room for the lanes past the end, like the AVX2 version.
+        let compacted_output = _mm512_maskz_compress_epi32(keeper_bitset, word);
+        _mm512_storeu_si512(output_tail as *mut __m512i, compacted_output);
+        output_tail = output_tail.offset(added_len as isize);
+        input = input.offset(1);
+    }
+    output_tail.offset_from(output) as usize
+}
+
+/// The lanes of `val` which share no letters with `charset_simd` and come after `last_added_simd`, as a mask.
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn compute_filter_bitset(
+    val: __m512i,
+    charset_simd: __m512i,
+    last_added_simd: __m512i,
+) -> __mmask16 {
+    let disjoint = _mm512_testn_epi32_mask(val, charset_simd);
+    _mm512_mask_cmpgt_epu32_mask(disjoint, val, last_added_simd)
+}
@@ -1,35 +1,48 @@
 #[cfg(not(windows))]
 mod filter_vec_avx2;
+#[cfg(not(windows))]
+mod filter_vec_avx512;
 
-use crate::LowerAsciiCharset;
+use crate::charset::{Charset, LowerAsciiCharset};
 
 pub fn filter_vec(
     input: &[LowerAsciiCharset],
     charset: LowerAsciiCharset,
     last_added: LowerAsciiCharset,
+    max_shared: u32,
 ) -> Vec<LowerAsciiCharset> {
     // For some reason, the avx2 version of this is much slower on windows for me, but
     // on linux (via WSL2) it is a bit faster, and it was a fun exercise.
     #[cfg(not(windows))]
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
+        // Counting shared letters 16 lanes at a time would need AVX-512 VPOPCNTDQ, which is much rarer, so
+        // only sharing nothing gets the wider kernel.
+        if max_shared == 0 && is_x86_feature_detected!("avx512f") {
+            return unsafe { filter_vec_avx512(input, charset, last_added) };
+        }
         if is_x86_feature_detected!("avx2") {
-            return unsafe { filter_vec_avx2(input, charset, last_added) };
+            // Sharing nothing is by far the most common case, and doesn't need to count letters.
+            return match max_shared {
+                0 => unsafe { filter_vec_avx2(input, charset, last_added) },
+                _ => unsafe { filter_vec_shared_avx2(input, charset, last_added, max_shared) },
+            };
         }
     }
 
-    filter_vec_scalar(input, charset, last_added)
+    filter_vec_scalar(input, charset, last_added, max_shared)
 }
 
-pub fn filter_vec_scalar(
-    input: &[LowerAsciiCharset],
-    charset: LowerAsciiCharset,
-    last_added: LowerAsciiCharset,
-) -> Vec<LowerAsciiCharset> {
+pub fn filter_vec_scalar<C: Charset>(
+    input: &[C],
+    charset: C,
+    last_added: C,
+    max_shared: u32,
+) -> Vec<C> {
     input
         .iter()
         .copied()
-        .filter(|c| !c.intersects(charset) && c > &last_added)
+        .filter(|c| c.shared(charset) <= max_shared && c > &last_added)
         .collect()
 }
 
@@ -43,3 +56,103 @@ unsafe fn filter_vec_avx2(
 ) -> Vec<LowerAsciiCharset> {
     filter_vec_avx2::filter_vec_avx2(input, charset, last_added)
 }
+
+#[cfg(not(windows))]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn filter_vec_shared_avx2(
+    input: &[LowerAsciiCharset],
+    charset: LowerAsciiCharset,
+    last_added: LowerAsciiCharset,
+    max_shared: u32,
+) -> Vec<LowerAsciiCharset> {
+    filter_vec_avx2::filter_vec_shared_avx2(input, charset, last_added, max_shared)
+}
+
+#[cfg(not(windows))]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx512f")]
+unsafe fn filter_vec_avx512(
+    input: &[LowerAsciiCharset],
+    charset: LowerAsciiCharset,
+    last_added: LowerAsciiCharset,
+) -> Vec<LowerAsciiCharset> {
+    filter_vec_avx512::filter_vec_avx512(input, charset, last_added)
+}
+
+#[cfg(test)]
+#[cfg(not(windows))]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod tests {
+    use super::*;
+    use crate::charset::WideCharset;
+
+    fn charset(bits: u32) -> LowerAsciiCharset {
+        LowerAsciiCharset::from_wide(WideCharset::from_bits(bits.into()))
+    }
+
+    /// `len` arbitrary but repeatable charsets of the first 26 letters, with `last_added` and the charsets
+    /// either side of it in among them.
+    fn inputs(len: usize, last_added: u32) -> Vec<LowerAsciiCharset> {
+        let mut state = 0x2545_f491_u32;
+        let mut input: Vec<_> = (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                charset(state & ((1 << 26) - 1))
+            })
+            .collect();
+        for (i, bits) in [last_added, last_added - 1, last_added + 1]
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(c) = input.get_mut(i * 5 + 2) {
+                *c = charset(bits);
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn shared_avx2_matches_scalar() {
+        if !is_x86_feature_detected!("avx2") {
+            return;
+        }
+        for len in [8, 9, 16, 23, 40] {
+            for last_added in [0b11, 0b1100_0000, 0x0155_0000] {
+                let input = inputs(len, last_added);
+                let last_added = charset(last_added);
+                for (charset, max_shared) in [
+                    (last_added, 2),
+                    (charset(0x00ff_00ff), 1),
+                    (charset(0x0fff), 3),
+                ] {
+                    let expected = filter_vec_scalar(&input, charset, last_added, max_shared);
+                    let actual =
+                        unsafe { filter_vec_shared_avx2(&input, charset, last_added, max_shared) };
+                    assert_eq!(actual, expected, "len {len}, max_shared {max_shared}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn avx512_matches_scalar() {
+        if !is_x86_feature_detected!("avx512f") {
+            return;
+        }
+        // Either side of a whole number of 16-lane blocks, so that the excess is checked too.
+        for len in [0, 15, 16, 17, 33] {
+            for last_added in [0b11, 0b1100_0000, 0x0155_0000] {
+                let input = inputs(len, last_added);
+                let last_added = charset(last_added);
+                for charset in [charset(0), charset(0x00ff_00ff), charset(0x0fff)] {
+                    let expected = filter_vec_scalar(&input, charset, last_added, 0);
+                    let actual = unsafe { filter_vec_avx512(&input, charset, last_added) };
+                    assert_eq!(actual, expected, "len {len}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! How common words are, so that solutions made of familiar words can be preferred.
+
+use rustc_hash::FxHashMap;
+use std::{
+    fmt,
+    io::{self, BufRead},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{alphabet::Alphabet, input, score::ByFrequency, sentence::Sentence, word::Word};
+
+/// How often each word occurs, e.g. in some corpus. Only the relative sizes matter.
+#[derive(Clone, Debug, Default)]
+pub struct Frequencies {
+    counts: FxHashMap<Word, f64>,
+}
+
+impl Frequencies {
+    /// Read a frequency list (which may be compressed or `-` for stdin, see [`input::open`]). Each line is a
+    /// word and how often it occurs, separated by whitespace or a comma, e.g. `fjord,12345`. Lines which
+    /// aren't, such as a header, are skipped, and the counts of words which differ only by case are added up.
+    pub fn from_path(path: impl AsRef<Path>, alphabet: &Alphabet) -> io::Result<Self> {
+        Self::from_reader(input::open(path)?, alphabet)
+    }
+
+    /// Read a frequency list in the format described in [`Self::from_path`].
+    pub fn from_reader(reader: impl BufRead, alphabet: &Alphabet) -> io::Result<Self> {
+        let mut counts = FxHashMap::default();
+        for line in reader.lines() {
+            let line = line?;
+            let mut fields = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|f| !f.is_empty());
+            let (Some(word), Some(count), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let (Some(w), Ok(count)) = (alphabet.parse(word), count.parse::<f64>()) else {
+                continue;
+            };
+            if count.is_finite() && count >= 0.0 {
+                *counts.entry(w).or_insert(0.0) += count;
+            }
+        }
+        Ok(Self { counts })
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// How often `w` occurs, which is 0 if it isn't in the list.
+    pub fn get(&self, w: Word) -> f64 {
+        self.counts.get(&w).copied().unwrap_or(0.0)
+    }
+
+    /// How familiar the words of `sentence` are, by the frequency of each of them.
+    pub fn score<const N: usize>(&self, sentence: &Sentence<N>, by: Familiarity) -> f64 {
+        sentence.score(&ByFrequency(self, by))
+    }
+}
+
+/// How to combine the frequencies of a sentence's words into one score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Familiarity {
+    /// The frequency of the rarest word, so a sentence is only as familiar as its most obscure word.
+    Min,
+    /// The geometric mean, so one obscure word can be made up for by the others being common.
+    GeometricMean,
+}
+
+impl FromStr for Familiarity {
+    type Err = UnknownFamiliarity;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "min" => Ok(Familiarity::Min),
+            "mean" => Ok(Familiarity::GeometricMean),
+            _ => Err(UnknownFamiliarity(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownFamiliarity(pub String);
+
+impl fmt::Display for UnknownFamiliarity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` isn't one of `min` or `mean`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFamiliarity {}
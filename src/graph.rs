@@ -0,0 +1,719 @@
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+use std::{
+    borrow::Cow,
+    io,
+    iter::Sum,
+    ops::{Add, ControlFlow},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    cache::{Decoder, Encoder},
+    charset::{Charset, LowerAsciiCharset},
+    score::TopK,
+    sentence::CharsetSentence,
+    solver::{branch, Split},
+};
+
+/// Maps each word to the words it shares no letters with (or, with [`Sharing`], few enough letters).
+pub struct WordGraph<C = LowerAsciiCharset> {
+    adj: FxHashMap<C, Vec<C>>,
+    sharing: Sharing,
+}
+
+impl<C: Charset> WordGraph<C> {
+    pub fn new(words: Vec<C>) -> Self {
+        Self::with_sharing(words, Sharing::default())
+    }
+
+    /// Like [`Self::new`], but the solutions can share as many letters as `sharing` allows.
+    pub fn with_sharing(words: Vec<C>, sharing: Sharing) -> Self {
+        let max_shared = sharing.max_pair();
+        let adj = words
+            .par_iter()
+            .map(|&charset| {
+                let words: Vec<C> = words
+                    .iter()
+                    .copied()
+                    .filter(|c| *c != charset && charset.shared(*c) <= max_shared)
+                    .collect();
+                (charset, words)
+            })
+            .collect();
+        Self { adj, sharing }
+    }
+
+    pub fn sharing(&self) -> Sharing {
+        self.sharing
+    }
+
+    pub fn neighbours(&self, word: C) -> &[C] {
+        self.adj.get(&word).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn encode(&self, enc: &mut Encoder) {
+        let (tag, k) = match self.sharing {
+            Sharing::Pairwise(k) => (0, k),
+            Sharing::Total(k) => (1, k),
+        };
+        enc.u8(tag);
+        enc.u32(k);
+        enc.count(self.adj.len());
+        for (&charset, nbs) in &self.adj {
+            enc.charset(charset);
+            enc.count(nbs.len());
+            nbs.iter().for_each(|&c| enc.charset(c));
+        }
+    }
+
+    pub(crate) fn decode(dec: &mut Decoder) -> io::Result<Self> {
+        let sharing = match (dec.u8()?, dec.u32()?) {
+            (0, k) => Sharing::Pairwise(k),
+            (_, k) => Sharing::Total(k),
+        };
+        let mut adj = FxHashMap::default();
+        for _ in 0..dec.count()? {
+            let charset = dec.charset()?;
+            let nbs = (0..dec.count()?)
+                .map(|_| dec.charset())
+                .collect::<io::Result<_>>()?;
+            adj.insert(charset, nbs);
+        }
+        Ok(Self { adj, sharing })
+    }
+
+    /// Find every set of words that share no letters (or as many as [`Self::sharing`] allows) and satisfy
+    /// `goal`, modulo anagram.
+    pub fn search<const N: usize>(&self, goal: Goal) -> Vec<CharsetSentence<N, C>> {
+        self.complete(CharsetSentence::new(), goal, C::default())
+    }
+
+    /// Find every way of adding words to `seed` so that they share no letters, satisfy `goal` and use all of
+    /// the `required` letters, modulo anagram.
+    pub fn complete<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.complete_among(seed, &self.candidates(seed), goal, required)
+    }
+
+    /// Like [`Self::complete`], but calls `f` with each solution as soon as it's found instead of collecting
+    /// them. `f` is called from many threads at once, and the search stops early once it returns
+    /// [`ControlFlow::Break`].
+    pub fn complete_each<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        f: impl Fn(CharsetSentence<N, C>) -> ControlFlow<()> + Sync,
+    ) -> ControlFlow<()> {
+        self.complete_among_each(seed, &self.candidates(seed), goal, required, f)
+    }
+
+    /// Like [`Self::complete`], but only adding words from `candidates`, which must be allowed to go with
+    /// `seed` (e.g. as found by [`Self::common_neighbours`]).
+    pub fn complete_among<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        candidates: &[C],
+        goal: Goal,
+        required: C,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.split_among(seed, candidates, goal, required)
+            .complete()
+    }
+
+    /// Like [`Self::complete_among`], but calls `f` with each solution as in [`Self::complete_each`].
+    pub fn complete_among_each<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        candidates: &[C],
+        goal: Goal,
+        required: C,
+        f: impl Fn(CharsetSentence<N, C>) -> ControlFlow<()> + Sync,
+    ) -> ControlFlow<()> {
+        self.split_among(seed, candidates, goal, required).each(f)
+    }
+
+    /// Split up the search [`Self::complete_among`] does, with a branch for each word it could start with.
+    pub(crate) fn split_among<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        candidates: &[C],
+        goal: Goal,
+        required: C,
+    ) -> Split<'_, N, C> {
+        let mut sols = vec![];
+        if is_sol(&seed, goal, required) {
+            sols.push(seed);
+        }
+        if seed.len() >= N {
+            return Split {
+                sols,
+                branches: vec![],
+            };
+        }
+
+        let forced_letter = self.forced_letter(seed, candidates, required);
+        let forced = forced_letter.is_some();
+        let branches = candidates
+            .iter()
+            .filter(|w| forced_letter.is_none_or(|l| w.contains(l)))
+            .map(|&w| {
+                branch(move |mut emit| {
+                    self.complete_from(seed, w, forced, goal, required, &mut emit)
+                })
+            })
+            .collect();
+        Split { sols, branches }
+    }
+
+    /// Count the solutions [`Self::complete`] would find without collecting them. Each one also counts as
+    /// the product of `weight` over its words as sentences, e.g. the sizes of their anagram classes.
+    pub fn count<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        weight: impl Fn(C) -> u64 + Sync,
+    ) -> Count {
+        let mut count = Count::default();
+        if is_sol(&seed, goal, required) {
+            count.add_sol(sentence_weight(&seed, &weight));
+        }
+        if seed.len() >= N {
+            return count;
+        }
+
+        let candidates = self.candidates(seed);
+        let forced_letter = self.forced_letter(seed, &candidates, required);
+        count
+            + candidates
+                .par_iter()
+                .filter(|w| forced_letter.is_none_or(|l| w.contains(l)))
+                .map(|&w| {
+                    let mut count = Count::default();
+                    let init = seed.add(w);
+                    if is_sol(&init, goal, required) {
+                        count.add_sol(sentence_weight(&init, &weight));
+                    }
+                    let last_added = match forced_letter {
+                        Some(_) => C::default(),
+                        None => w,
+                    };
+                    if init.len() < N {
+                        let nbs = &self.start_nbs(seed, w);
+                        let sharing = self.sharing;
+                        count_sols(
+                            &mut count, init, last_added, nbs, goal, required, sharing, &weight,
+                        );
+                    }
+                    count
+                })
+                .sum::<Count>()
+    }
+
+    /// Find the `k` best solutions [`Self::complete`] would find, best first. A solution's score is `combine`d
+    /// from the `score`s of its words, and `combine` must never decrease when one of its arguments increases,
+    /// so that branches which can't beat the `k`th best solution so far can be skipped.
+    pub fn best<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        k: usize,
+        score: impl Fn(C) -> f64 + Sync,
+        combine: impl Fn(&[f64]) -> f64 + Sync,
+    ) -> Vec<(f64, CharsetSentence<N, C>)> {
+        let mut seed_scores = [0.0; N];
+        for (s, w) in seed_scores.iter_mut().zip(seed.words()) {
+            *s = score(*w);
+        }
+        let scores: FxHashMap<C, f64> = self.adj.par_iter().map(|(&c, _)| (c, score(c))).collect();
+        let best_nbs = |nbs: &[C]| {
+            nbs.iter()
+                .map(|c| scores[c])
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        let ranker = Ranker {
+            scores: (self.adj.par_iter())
+                .map(|(&c, nbs)| (c, (scores[&c], best_nbs(nbs))))
+                .collect(),
+            combine: &combine,
+            floor: AtomicU64::new(0),
+            goal,
+            required,
+            sharing: self.sharing,
+        };
+        let mut top = TopK::new(k);
+        if is_sol(&seed, goal, required) {
+            ranker.offer(&mut top, (seed, seed_scores));
+        }
+        if seed.len() >= N {
+            return top.into_sorted_vec();
+        }
+
+        let mut candidates = self.candidates(seed);
+        let forced_letter = self.forced_letter(seed, &candidates, required);
+        // Searching from the best words first finds good solutions early, so that more of the rest is skipped.
+        candidates.retain(|w| forced_letter.is_none_or(|l| w.contains(l)));
+        candidates.sort_by(|a, b| ranker.scores[b].0.total_cmp(&ranker.scores[a].0));
+        candidates
+            .par_iter()
+            .map(|&w| {
+                let mut top = TopK::new(k);
+                let mut init_scores = seed_scores;
+                let (w_score, best_nb) = ranker.scores[&w];
+                init_scores[seed.len()] = w_score;
+                let init = (seed.add(w), init_scores);
+                if is_sol(&init.0, goal, required) {
+                    ranker.offer(&mut top, init);
+                }
+                let last_added = match forced_letter {
+                    Some(_) => C::default(),
+                    None => w,
+                };
+                if init.0.len() < N {
+                    let nbs = &self.start_nbs(seed, w);
+                    ranker.find_best(&mut top, init, last_added, nbs, best_nb);
+                }
+                top
+            })
+            .reduce(|| TopK::new(k), TopK::merge)
+            .merge(top)
+            .into_sorted_vec()
+    }
+
+    /// The words which could be added to `seed`.
+    pub(crate) fn candidates<const N: usize>(&self, seed: CharsetSentence<N, C>) -> Vec<C> {
+        self.adj
+            .keys()
+            .copied()
+            .filter(|c| self.sharing.allows(seed.words(), *c))
+            .collect()
+    }
+
+    /// The neighbours of `w` which could be added to `seed` along with it. Only pairwise sharing needs
+    /// them checked against `seed` up front, since otherwise each step compares them with every letter used.
+    fn start_nbs<const N: usize>(&self, seed: CharsetSentence<N, C>, w: C) -> Cow<'_, [C]> {
+        let nbs = self.neighbours(w);
+        match self.sharing {
+            Sharing::Pairwise(k) if k > 0 && !seed.is_empty() => nbs
+                .iter()
+                .copied()
+                .filter(|c| self.sharing.allows(seed.words(), *c))
+                .collect(),
+            _ => Cow::Borrowed(nbs),
+        }
+    }
+
+    /// Exactly one word of each solution uses a given required letter, so if there are any we can start from
+    /// each of the (few) words which use the rarest one and allow the rest in any order.
+    fn forced_letter<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        candidates: &[C],
+        required: C,
+    ) -> Option<u8> {
+        // If words can share letters, several of them could use it.
+        if !self.sharing.is_strict() {
+            return None;
+        }
+        required
+            .difference(seed.charset())
+            .letters()
+            .min_by_key(|&l| candidates.iter().filter(|c| c.contains(l)).count())
+    }
+
+    /// Find the solutions which extend `seed` with `w` and then words from its neighbours. If `forced` then
+    /// `w` is the only word using the forced letter, and the rest can come in any order.
+    fn complete_from<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        w: C,
+        forced: bool,
+        goal: Goal,
+        required: C,
+        emit: &mut impl FnMut(CharsetSentence<N, C>),
+    ) {
+        let init = seed.add(w);
+        if is_sol(&init, goal, required) {
+            emit(init);
+        }
+        let last_added = match forced {
+            true => C::default(),
+            false => w,
+        };
+        if init.len() < N {
+            let nbs = &self.start_nbs(seed, w);
+            find_sols(emit, init, last_added, nbs, goal, required, self.sharing);
+        }
+    }
+
+    /// The words which could be added to a set of `words`, i.e. the ones in all of their neighbour lists which
+    /// [`Self::sharing`] allows.
+    pub fn common_neighbours(&self, words: &[C]) -> Vec<C> {
+        let Some(shortest) = words
+            .iter()
+            .map(|w| self.neighbours(*w))
+            .min_by_key(|nbs| nbs.len())
+        else {
+            return self.adj.keys().copied().collect();
+        };
+
+        // Every other list contains exactly the words which share few enough letters with its word.
+        shortest
+            .iter()
+            .copied()
+            .filter(|c| self.sharing.allows(words, *c))
+            .collect()
+    }
+}
+
+/// When a set of words counts as a solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// Exactly `N` words, whatever their lengths.
+    Words,
+    /// Any number of words up to `N` which together use at least this many letters.
+    Letters(u32),
+}
+
+impl Goal {
+    pub fn is_met<const N: usize, C: Charset>(&self, sol: &CharsetSentence<N, C>) -> bool {
+        match *self {
+            Goal::Words => sol.len() == N,
+            Goal::Letters(letters) => sol.charset().len() >= letters,
+        }
+    }
+}
+
+/// How many letters the words of a solution may have in common.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sharing {
+    /// Any two of the words may share at most this many letters.
+    Pairwise(u32),
+    /// The words may use letters again at most this many times in total, e.g. `fjord chord` uses 3 again.
+    Total(u32),
+}
+
+impl Default for Sharing {
+    /// No letter can be used twice.
+    fn default() -> Self {
+        Sharing::Pairwise(0)
+    }
+}
+
+impl Sharing {
+    /// Whether no letter can be used twice, which is the usual search.
+    pub fn is_strict(&self) -> bool {
+        self.max_pair() == 0
+    }
+
+    /// The most letters any two words of a solution can share.
+    pub fn max_pair(&self) -> u32 {
+        match *self {
+            Sharing::Pairwise(k) | Sharing::Total(k) => k,
+        }
+    }
+
+    /// Whether `c` could be added to a set of `words`.
+    pub fn allows<C: Charset>(&self, words: &[C], c: C) -> bool {
+        match *self {
+            Sharing::Pairwise(k) => words.iter().all(|w| w.shared(c) <= k),
+            Sharing::Total(k) => {
+                let mut charset = C::default();
+                words.iter().for_each(|w| charset.union(*w));
+                k.checked_sub(repeats(words))
+                    .is_some_and(|left| charset.shared(c) <= left)
+            }
+        }
+    }
+
+    /// The `nbs` which could be added to `sol` and come after `last_added`, its last word. They must all have
+    /// been allowed with the rest of `sol`, so that pairwise sharing only has to compare them with that word.
+    fn filter<const N: usize, C: Charset>(
+        &self,
+        nbs: &[C],
+        sol: CharsetSentence<N, C>,
+        last_added: C,
+    ) -> Vec<C> {
+        match *self {
+            Sharing::Pairwise(0) | Sharing::Total(0) => {
+                C::filter_vec(nbs, sol.charset(), last_added, 0)
+            }
+            Sharing::Pairwise(k) => C::filter_vec(nbs, last_added, last_added, k),
+            Sharing::Total(k) => match k.checked_sub(repeats(sol.words())) {
+                Some(left) => C::filter_vec(nbs, sol.charset(), last_added, left),
+                None => vec![],
+            },
+        }
+    }
+}
+
+/// How many times `words` use a letter which another of them already used.
+fn repeats<C: Charset>(words: &[C]) -> u32 {
+    let mut charset = C::default();
+    words.iter().for_each(|w| charset.union(*w));
+    words.iter().map(|w| w.len()).sum::<u32>() - charset.len()
+}
+
+pub(crate) fn is_sol<const N: usize, C: Charset>(
+    sol: &CharsetSentence<N, C>,
+    goal: Goal,
+    required: C,
+) -> bool {
+    !sol.is_empty() && goal.is_met(sol) && sol.charset().is_superset(required)
+}
+
+/// Whether even using every one of `nbs` couldn't turn `cur_sol` into a solution.
+fn unreachable<const N: usize, C: Charset>(
+    cur_sol: CharsetSentence<N, C>,
+    nbs: &[C],
+    goal: Goal,
+    required: C,
+) -> bool {
+    let letters = match goal {
+        Goal::Words => None,
+        Goal::Letters(letters) => Some(letters),
+    };
+    if letters.is_none() && cur_sol.charset().is_superset(required) {
+        return false;
+    }
+    let mut reachable = cur_sol.charset();
+    nbs.iter().for_each(|c| reachable.union(*c));
+    !reachable.is_superset(required) || letters.is_some_and(|l| reachable.len() < l)
+}
+
+fn find_sols<const N: usize, C: Charset>(
+    emit: &mut impl FnMut(CharsetSentence<N, C>),
+    cur_sol: CharsetSentence<N, C>,
+    last_added: C,
+    nbs: &[C],
+    goal: Goal,
+    required: C,
+    sharing: Sharing,
+) {
+    // Words are only ever added in increasing charset order, so each set is found exactly once no matter how
+    // the lengths of its words are mixed.
+    let nbs = sharing.filter(nbs, cur_sol, last_added);
+    if nbs.is_empty() {
+        return;
+    }
+
+    if unreachable(cur_sol, &nbs, goal, required) {
+        return;
+    }
+
+    let sols_to_explore = nbs.iter().map(|&c| (c, cur_sol.add(c)));
+
+    match goal {
+        Goal::Words => {
+            if cur_sol.len() + 1 >= N {
+                sols_to_explore
+                    .map(|(_, s)| s)
+                    .filter(|s| s.charset().is_superset(required))
+                    .for_each(&mut *emit);
+            } else {
+                sols_to_explore.for_each(|(c, sol)| {
+                    find_sols(emit, sol, c, &nbs, goal, required, sharing);
+                });
+            }
+        }
+        Goal::Letters(_) => {
+            sols_to_explore.for_each(|(c, sol)| {
+                if goal.is_met(&sol) && sol.charset().is_superset(required) {
+                    emit(sol);
+                }
+                if sol.len() < N {
+                    find_sols(emit, sol, c, &nbs, goal, required, sharing);
+                }
+            });
+        }
+    }
+}
+
+/// The product of `weight` over the words of `sol`.
+fn sentence_weight<const N: usize, C: Charset>(
+    sol: &CharsetSentence<N, C>,
+    weight: &impl Fn(C) -> u64,
+) -> u64 {
+    sol.words().iter().map(|w| weight(*w)).product()
+}
+
+/// Like [`find_sols`], but only counts the solutions. Their weights are only worked out once they're found,
+/// since most of the partial solutions searched never lead to one.
+#[allow(clippy::too_many_arguments)]
+fn count_sols<const N: usize, C: Charset>(
+    count: &mut Count,
+    cur_sol: CharsetSentence<N, C>,
+    last_added: C,
+    nbs: &[C],
+    goal: Goal,
+    required: C,
+    sharing: Sharing,
+    weight: &impl Fn(C) -> u64,
+) {
+    let nbs = sharing.filter(nbs, cur_sol, last_added);
+    if nbs.is_empty() || unreachable(cur_sol, &nbs, goal, required) {
+        return;
+    }
+
+    match goal {
+        // Every remaining word finishes a solution, so there's no need to build them.
+        Goal::Words if cur_sol.len() + 1 >= N => {
+            let missing = required.difference(cur_sol.charset());
+            let last = nbs.iter().filter(|c| c.is_superset(missing));
+            let (sets, weights) = last.fold((0, 0), |(n, sum), &c| (n + 1, sum + weight(c)));
+            if sets > 0 {
+                count.sets += sets;
+                count.sentences += sentence_weight(&cur_sol, weight) * weights;
+            }
+        }
+        Goal::Words => {
+            for &c in &nbs {
+                let next = cur_sol.add(c);
+                count_sols(count, next, c, &nbs, goal, required, sharing, weight);
+            }
+        }
+        Goal::Letters(_) => {
+            for &c in &nbs {
+                let sol = cur_sol.add(c);
+                if goal.is_met(&sol) && sol.charset().is_superset(required) {
+                    count.add_sol(sentence_weight(&sol, weight));
+                }
+                if sol.len() < N {
+                    count_sols(count, sol, c, &nbs, goal, required, sharing, weight);
+                }
+            }
+        }
+    }
+}
+
+/// What [`WordGraph::best`] needs to score partial solutions and decide which branches are worth searching.
+struct Ranker<'a, C, F> {
+    /// Each word's score, and the best score of any word it could go with.
+    scores: FxHashMap<C, (f64, f64)>,
+    combine: &'a F,
+    /// The best `k`th best score any thread has found so far, as [`ordered_bits`].
+    floor: AtomicU64,
+    goal: Goal,
+    required: C,
+    sharing: Sharing,
+}
+
+impl<C: Charset, F: Fn(&[f64]) -> f64> Ranker<'_, C, F> {
+    /// Like [`find_sols`], but only keeping the best solutions in `top`, and `cur_sol` comes with the scores of
+    /// its words so far. `best_nb` is the best score of any word that can go with all of the words it's added
+    /// to the seed.
+    fn find_best<const N: usize>(
+        &self,
+        top: &mut TopK<CharsetSentence<N, C>>,
+        (cur_sol, cur_scores): (CharsetSentence<N, C>, [f64; N]),
+        last_added: C,
+        nbs: &[C],
+        best_nb: f64,
+    ) {
+        // No solution from here can do better than giving every remaining word the best score on offer. Every
+        // word that could be added goes with each word added so far, so that's at most `best_nb`, which can be
+        // checked before working out which words those are.
+        let mut scores = cur_scores;
+        scores[cur_sol.len()..].fill(best_nb);
+        let fewest = match self.goal {
+            Goal::Words => N,
+            Goal::Letters(_) => cur_sol.len() + 1,
+        };
+        let bound = (fewest..=N)
+            .map(|n| (self.combine)(&scores[..n]))
+            .fold(f64::NEG_INFINITY, f64::max);
+        if self.cannot_beat(top, bound) {
+            return;
+        }
+
+        let nbs = self.sharing.filter(nbs, cur_sol, last_added);
+        if nbs.is_empty() || unreachable(cur_sol, &nbs, self.goal, self.required) {
+            return;
+        }
+
+        for &c in &nbs {
+            let (score, c_best_nb) = self.scores[&c];
+            let mut scores = cur_scores;
+            scores[cur_sol.len()] = score;
+            let sol = cur_sol.add(c);
+            if self.goal.is_met(&sol) && sol.charset().is_superset(self.required) {
+                self.offer(top, (sol, scores));
+            }
+            if sol.len() < N {
+                self.find_best(top, (sol, scores), c, &nbs, best_nb.min(c_best_nb));
+            }
+        }
+    }
+
+    fn offer<const N: usize>(
+        &self,
+        top: &mut TopK<CharsetSentence<N, C>>,
+        (sol, scores): (CharsetSentence<N, C>, [f64; N]),
+    ) {
+        let score = (self.combine)(&scores[..sol.len()]);
+        if self.cannot_beat(top, score) {
+            return;
+        }
+        top.push(score, sol);
+        if let Some(floor) = top.floor() {
+            self.floor.fetch_max(ordered_bits(floor), Ordering::Relaxed);
+        }
+    }
+
+    /// Whether `score` is no better than the `k`th best score found so far, by this thread or any other.
+    fn cannot_beat<T>(&self, top: &TopK<T>, score: f64) -> bool {
+        let shared = self.floor.load(Ordering::Relaxed);
+        top.floor().is_some_and(|floor| score <= floor)
+            || (shared != 0 && ordered_bits(score) <= shared)
+    }
+}
+
+/// The bits of `x`, flipped so that they compare as integers in the same order as [`f64::total_cmp`]. Only
+/// a negative NaN with every bit set maps to 0, so 0 can mean there's no score yet.
+fn ordered_bits(x: f64) -> u64 {
+    let bits = x.to_bits();
+    match bits >> 63 {
+        1 => !bits,
+        _ => bits | 1 << 63,
+    }
+}
+
+/// How many solutions a search finds, both modulo anagram and as sentences.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Count {
+    /// Solutions as sets of charsets, i.e. modulo anagram.
+    pub sets: u64,
+    /// Solutions as sentences, i.e. weighted by the product of their words' weights.
+    pub sentences: u64,
+}
+
+impl Count {
+    pub(crate) fn add_sol(&mut self, weight: u64) {
+        self.sets += 1;
+        self.sentences += weight;
+    }
+}
+
+impl Add for Count {
+    type Output = Count;
+
+    fn add(self, other: Count) -> Count {
+        Count {
+            sets: self.sets + other.sets,
+            sentences: self.sentences + other.sentences,
+        }
+    }
+}
+
+impl Sum for Count {
+    fn sum<I: Iterator<Item = Count>>(iter: I) -> Count {
+        iter.fold(Count::default(), Add::add)
+    }
+}
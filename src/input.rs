@@ -0,0 +1,127 @@
+//! Opening word lists, which can come from stdin and can be compressed.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+/// The path which means stdin rather than a file.
+pub const STDIN: &str = "-";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Open `path` for reading, or stdin if it's `-`. Gzip and zstd streams are decompressed, going by their
+/// contents rather than the file name, so that compressed lists can be piped in too.
+pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    if path == Path::new(STDIN) {
+        decompress(io::stdin().lock())
+    } else {
+        decompress(BufReader::new(File::open(path)?))
+    }
+}
+
+fn decompress(mut reader: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        gzip(reader)
+    } else if start.starts_with(ZSTD_MAGIC) {
+        zstd(reader)
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(reader: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    // Concatenated gzip files are still one valid gzip file, so read all of the members.
+    let decoder = flate2::bufread::MultiGzDecoder::new(reader);
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip(_: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    Err(unsupported("gzip"))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd(reader: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    let decoder = zstd::Decoder::with_buffer(reader)?;
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd(_: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    Err(unsupported("zstd"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn unsupported(format: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("compressed with {format}, but built without the `{format}` feature"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const WORDS: &str = "fjord\nwaltz\nvibex\n";
+
+    fn read(mut reader: Box<dyn BufRead>) -> String {
+        let mut s = String::new();
+        reader.read_to_string(&mut s).unwrap();
+        s
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(read(decompress(WORDS.as_bytes()).unwrap()), WORDS);
+        // Too short to be checked for either magic number.
+        assert_eq!(read(decompress(&b"\x1f"[..]).unwrap()), "\x1f");
+        assert_eq!(read(decompress(&b""[..]).unwrap()), "");
+    }
+
+    /// Compressed lists are recognised by their contents, whatever they're called.
+    #[cfg(all(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn compressed_by_magic() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::{fs, io::Write};
+
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(WORDS.as_bytes()).unwrap();
+        let gz = gz.finish().unwrap();
+        let zst = zstd::encode_all(WORDS.as_bytes(), 0).unwrap();
+        assert!(gz.starts_with(GZIP_MAGIC) && zst.starts_with(ZSTD_MAGIC));
+
+        let dir = tempfile::tempdir().unwrap();
+        for (name, bytes) in [
+            ("plain.gz", WORDS.as_bytes()),
+            ("gzip.txt", &gz),
+            ("zstd.txt", &zst),
+        ] {
+            let path = dir.path().join(name);
+            fs::write(&path, bytes).unwrap();
+            assert_eq!(read(open(&path).unwrap()), WORDS, "{name}");
+        }
+
+        // Concatenated gzip files read as one.
+        let twice = [&gz[..], &gz[..]].concat();
+        assert_eq!(
+            read(decompress(io::Cursor::new(twice)).unwrap()),
+            WORDS.repeat(2)
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = open(dir.path().join("missing.txt")).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
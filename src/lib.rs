@@ -0,0 +1,54 @@
+//! Find sets of words which share no letters, e.g. five five-letter words using 25 distinct letters.
+//!
+//! ```no_run
+//! use five_clique::{Dictionary, Goal};
+//!
+//! let dict = Dictionary::from_path("words_alpha.txt", 5..=5)?;
+//! let sols = dict.build_graph().search::<5>(Goal::Words);
+//! for sentence in dict.expand_all(sols) {
+//!     println!("{}", sentence.as_string());
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod alphabet;
+pub mod bitset_graph;
+pub mod cache;
+pub mod charset;
+pub mod completion;
+pub mod constraints;
+pub mod dictionary;
+pub mod exact_cover;
+pub mod external_sort;
+mod filter_vec;
+pub mod frequency;
+pub mod graph;
+pub mod input;
+pub mod meet_in_the_middle;
+pub mod normalise;
+pub mod output;
+pub mod rarest_first;
+pub mod score;
+pub mod sentence;
+pub mod solver;
+pub mod word;
+pub mod word_list;
+
+pub use alphabet::Alphabet;
+pub use bitset_graph::BitsetGraph;
+pub use cache::{Cache, CacheKey};
+pub use charset::{Charset, LowerAsciiCharset, WideCharset, ALPHABET_LEN};
+pub use completion::Completer;
+pub use constraints::{ConstraintError, Constraints};
+pub use dictionary::Dictionary;
+pub use exact_cover::ExactCover;
+pub use frequency::{Familiarity, Frequencies};
+pub use graph::{Count, Goal, Sharing, WordGraph};
+pub use meet_in_the_middle::MeetInTheMiddle;
+pub use output::Format;
+pub use rarest_first::RarestFirst;
+pub use score::{Ranking, Scorer, TopK};
+pub use sentence::{CharsetSentence, Sentence};
+pub use solver::{Backend, Solver, Split};
+pub use word::Word;
+pub use word_list::WordList;
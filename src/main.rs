@@ -1,286 +1,681 @@
-mod filter_vec;
-
-use filter_vec::filter_vec;
-use itertools::Itertools;
-use rayon::prelude::*;
-use rustc_hash::FxHashMap;
+use clap::Parser;
+use five_clique::{
+    external_sort::ExternalSorter,
+    input,
+    normalise::{Normaliser, Report},
+    word::{Word, MAX_WORD_LEN},
+    Alphabet, Backend, Cache, CacheKey, Charset, CharsetSentence, Constraints, Dictionary, Format,
+    Frequencies, Goal, LowerAsciiCharset, Ranking, Sentence, Sharing, Solver, TopK, WideCharset,
+    WordGraph, WordList,
+};
 use std::{
-    fs::{File, self},
-    io::{BufRead, BufReader, Write},
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    ops::{ControlFlow, RangeInclusive},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc,
+    thread,
 };
 
-const WLEN: usize = 5;
-const SLEN: usize = 5;
-
-type Word = [u8; WLEN];
+/// Find sets of words that share no letters.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Word lists to search, one word per line, or `-` for stdin. Words from any of them are used, and
+    /// lists compressed with gzip or zstd are decompressed
+    #[arg(default_value = "words_alpha.txt")]
+    dictionaries: Vec<PathBuf>,
+
+    /// Only use words which are also in this word list (may be repeated)
+    #[arg(long)]
+    intersect: Vec<PathBuf>,
+
+    /// Write solutions to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Number of worker threads [default: one per CPU]
+    #[arg(short = 'j', long)]
+    threads: Option<usize>,
+
+    /// Number of letters in each word, either exactly (e.g. `5`) or as a range (e.g. `3-8`)
+    #[arg(short, long, default_value = "5", value_parser = parse_word_lens)]
+    word_len: RangeInclusive<usize>,
+
+    /// The letters words are made of: one of `en`, `de`, `es`, `pl`, `ru`, `uk` or `el`, or every letter
+    /// of the alphabet in order (e.g. `abcdefghijklmnopqrstuvwxyzåäö`)
+    #[arg(short, long, default_value = "en")]
+    alphabet: Alphabet,
+
+    /// Number of words in each solution, or the most words allowed with `--letters` or `--exact-cover`
+    /// [default: 5, or as many as could fit with `--letters` or `--exact-cover`]
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=MAX_SENTENCE_LEN as i64))]
+    sentence_len: Option<u8>,
+
+    /// Accept any number of words which together use at least this many letters
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=WideCharset::CAPACITY as i64))]
+    letters: Option<u32>,
+
+    /// Find sets of words which use every letter exactly once
+    #[arg(short = 'x', long, conflicts_with = "letters")]
+    exact_cover: bool,
+
+    /// How to search: `clique` (growing sets of words in order), `bitset` (the same, keeping which words can
+    /// go together as a bit matrix), `rarest-first` (using or leaving out each letter in turn, from the rarest
+    /// to the commonest), `meet-in-the-middle` (looking up the last two words among every pair of words) or
+    /// `exact-cover` [default: `exact-cover` with `--exact-cover`, otherwise `clique`]
+    #[arg(long, value_name = "NAME")]
+    solver: Option<Backend>,
+
+    /// Let any two words of a solution share up to this many letters
+    #[arg(long, value_name = "K", conflicts_with = "exact_cover")]
+    max_shared: Option<u32>,
+
+    /// Let the words of a solution use letters again up to this many times in total
+    #[arg(long, value_name = "K", conflicts_with_all = ["exact_cover", "max_shared"])]
+    max_repeats: Option<u32>,
+
+    /// Only find solutions containing this word (may be repeated)
+    #[arg(short, long)]
+    include: Vec<String>,
+
+    /// Don't use this word (may be repeated)
+    #[arg(short, long)]
+    exclude: Vec<String>,
+
+    /// Don't use any of the words in this word list (may be repeated)
+    #[arg(long, visible_alias = "subtract")]
+    exclude_from: Vec<PathBuf>,
+
+    /// Only find solutions using all of these letters
+    #[arg(long)]
+    require: Option<String>,
+
+    /// Only find solutions using none of these letters
+    #[arg(long)]
+    forbid: Option<String>,
+
+    /// Only keep solutions which leave all of these letters unused
+    #[arg(short, long)]
+    unused: Option<String>,
+
+    /// A list of how often each word occurs, with a word and a count on each line (e.g. `fjord,12345`), for
+    /// `--min-frequency` and `--rank`
+    #[arg(long)]
+    frequencies: Option<PathBuf>,
+
+    /// Don't use words which occur less often than this according to `--frequencies`
+    #[arg(long, requires = "frequencies")]
+    min_frequency: Option<f64>,
+
+    /// Write the best solutions first, scoring each one by the `min` or geometric `mean` of its words'
+    /// frequencies (which needs `--frequencies`), its total `length`, or its `scrabble` points
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "count_by_unused", "group_by_unused", "compact", "sort"]
+    )]
+    rank: Option<Ranking>,
+
+    /// Only find the best `K` solutions by `--rank`, skipping the parts of the search which can't beat them
+    #[arg(long, value_name = "K", requires = "rank")]
+    top: Option<usize>,
+
+    /// Prefix each solution with the letters it leaves unused, and sort by them (implies `--sort`)
+    #[arg(long)]
+    group_by_unused: bool,
+
+    /// Only print how many solutions leave each set of letters unused
+    #[arg(long, conflicts_with = "group_by_unused")]
+    count_by_unused: bool,
+
+    /// How to write each solution: `text` (space-separated words), `jsonl` (a JSON object with the
+    /// words, their anagram classes, the unused letters and the letters used) or `csv` (one column per word)
+    #[arg(short, long, default_value = "text")]
+    format: Format,
+
+    /// Write one line per set of anagram classes instead of expanding every combination of anagrams, with the
+    /// number of solutions it stands for
+    #[arg(short, long, conflicts_with = "count_by_unused")]
+    compact: bool,
+
+    /// Only print how many solutions there are, with and without expanding anagrams
+    #[arg(
+        long,
+        conflicts_with_all = ["count_by_unused", "group_by_unused", "compact", "sort"]
+    )]
+    count: bool,
+
+    /// Sort the solutions, instead of writing them out as soon as they're found
+    #[arg(long)]
+    sort: bool,
+
+    /// Memory to use for sorting before spilling to temporary files, in MiB
+    #[arg(long, default_value_t = 512, value_parser = clap::value_parser!(u32).range(1..))]
+    sort_memory: u32,
+
+    /// Take the accents off letters in the word lists, e.g. `café` becomes `cafe`
+    #[arg(long)]
+    strip_accents: bool,
+
+    /// Remove punctuation and spaces from the word lists, e.g. `don't` becomes `dont`
+    #[arg(long)]
+    strip_punctuation: bool,
+
+    /// Report how many lines of each word list were used, and why the others weren't, on stderr
+    #[arg(long)]
+    report: bool,
+
+    /// Keep the anagram classes and word graph built from the word lists in this directory, and load them
+    /// instead of building them again on later runs with the same word lists and options
+    #[arg(long, value_name = "DIR", conflicts_with = "report")]
+    cache: Option<PathBuf>,
+
+    /// Don't report progress on stderr
+    #[arg(short, long)]
+    quiet: bool,
+}
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
-pub struct LowerAsciiCharset(u32);
-impl From<Word> for LowerAsciiCharset {
-    fn from(w: Word) -> Self {
-        let mut chars = 0;
-        w.iter().for_each(|b| chars |= 1 << b);
-        Self(chars)
+/// How many solutions' worth of anagrams can be waiting to be written before the search blocks.
+const SOLUTION_BATCHES: usize = 1024;
+
+/// A solution found by the search, and the sentences it expands to unless only the compact form is needed.
+type Batch<const N: usize, C> = (CharsetSentence<N, C>, Vec<Sentence<N>>);
+
+/// The most words the solutions can have, which bounds the instantiations of `run`.
+const MAX_SENTENCE_LEN: usize = 13;
+
+fn parse_word_lens(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |n: &str| match n.trim().parse() {
+        Ok(n) if (1..=MAX_WORD_LEN).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "`{n}` isn't a word length from 1 to {MAX_WORD_LEN}"
+        )),
+    };
+    let (min, max) = match s.split_once('-') {
+        Some((min, max)) => (parse(min)?, parse(max)?),
+        None => (parse(s)?, parse(s)?),
+    };
+    if min > max {
+        return Err(format!("`{s}` is an empty range"));
     }
+    Ok(min..=max)
 }
 
-impl LowerAsciiCharset {
-    fn default() -> Self {
-        LowerAsciiCharset(0)
-    }
+// Words and letters given as arguments can only be parsed once the alphabet is known.
 
-    fn intersects(&self, other: Self) -> bool {
-        self.0 & other.0 != 0
+fn parse_letters<C: Charset>(alphabet: &Alphabet, s: Option<&str>) -> io::Result<C> {
+    let Some(s) = s else {
+        return Ok(C::default());
+    };
+    if s.is_empty() || !s.chars().all(|c| alphabet.contains(c)) {
+        return Err(invalid(format!("`{s}` isn't a set of letters")));
     }
+    alphabet
+        .parse(s)
+        .map(C::from)
+        .ok_or_else(|| invalid(format!("`{s}` has too many letters")))
+}
 
-    fn union(&mut self, other: Self) {
-        self.0 |= other.0
+fn parse_word(alphabet: &Alphabet, s: &str) -> io::Result<Word> {
+    if s.is_empty() || !s.chars().all(|c| alphabet.contains(c)) {
+        return Err(invalid(format!("`{s}` isn't a word")));
     }
+    alphabet
+        .parse(s)
+        .ok_or_else(|| invalid(format!("`{s}` is too long")))
 }
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
-struct Sentence<const N: usize> {
-    words: [Option<Word>; N],
-    len: u8,
+fn invalid(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, e)
 }
 
-impl<const N: usize> Sentence<N> {
-    fn new() -> Sentence<N> {
-        Sentence {
-            len: 0,
-            words: [None; N],
+/// Expands `$body` once for each of the listed values, with `$name` bound to the matching value as a
+/// `const`, so that a runtime parameter can be used as a const generic argument.
+macro_rules! with_const {
+    ($val:expr, $name:ident, [$($n:literal),*], $body:expr) => {
+        match $val {
+            $($n => {
+                const $name: usize = $n;
+                $body
+            })*
+            _ => unreachable!(),
         }
-    }
+    };
+}
 
-    fn add(mut self, w: Word) -> Self {
-        self.words[self.len as usize] = Some(w);
-        self.len += 1;
-        self
-    }
+/// The length of the prefix each line is sorted by with `--rank`, including the space after it.
+const RANK_KEY_LEN: usize = 17;
 
-    fn as_string(&self) -> String {
-        self.words.into_iter().flatten().map(show).join(" ")
-    }
+fn with_path(e: io::Error, path: &Path) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {e}", path.display()))
 }
 
-impl<I, const N: usize> From<I> for Sentence<N>
-where
-    I: IntoIterator<Item = Word>,
-{
-    fn from(words: I) -> Self {
-        let mut out = Self::new();
-        words.into_iter().for_each(|w| out = out.add(w));
-        out
-    }
+/// What happened to the lines of each word list, and then to the words of the combined list.
+type Reports = Vec<(String, Report)>;
+
+fn read_word_list(args: &Args, path: &Path, reports: &mut Reports) -> io::Result<WordList> {
+    let normaliser = Normaliser {
+        alphabet: args.alphabet.clone(),
+        strip_accents: args.strip_accents,
+        strip_punctuation: args.strip_punctuation,
+    };
+    let mut report = Report::default();
+    let words =
+        WordList::from_path_with(path, &normaliser, &mut report).map_err(|e| with_path(e, path))?;
+    reports.push((path.display().to_string(), report));
+    Ok(words)
 }
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
-struct CharsetSentence<const N: usize> {
-    words: [LowerAsciiCharset; N],
-    len: u8,
-    charset: LowerAsciiCharset,
+/// The union of the dictionaries, intersected with each of the `--intersect` lists.
+fn word_list(args: &Args, reports: &mut Reports) -> io::Result<WordList> {
+    let mut words = WordList::default();
+    for path in &args.dictionaries {
+        words.union(&read_word_list(args, path, reports)?);
+    }
+    for path in &args.intersect {
+        words.intersect(&read_word_list(args, path, reports)?);
+    }
+    Ok(words)
 }
 
-impl<const N: usize> CharsetSentence<N> {
-    fn new() -> CharsetSentence<N> {
-        CharsetSentence {
-            len: 0,
-            words: [LowerAsciiCharset::default(); N],
-            charset: LowerAsciiCharset::default(),
-        }
+fn constraints<C: Charset>(args: &Args, reports: &mut Reports) -> io::Result<Constraints<C>> {
+    let alphabet = &args.alphabet;
+    let words = |words: &[String]| -> io::Result<Vec<_>> {
+        words.iter().map(|w| parse_word(alphabet, w)).collect()
+    };
+    let mut constraints: Constraints<C> = Constraints {
+        include: words(&args.include)?,
+        exclude: words(&args.exclude)?.into_iter().collect(),
+        require_letters: parse_letters(alphabet, args.require.as_deref())?,
+        forbid_letters: parse_letters(alphabet, args.forbid.as_deref())?,
+        sharing: sharing(args),
+    };
+    // Leaving a letter unused is the same as forbidding it, and pruning is much faster than filtering.
+    constraints
+        .forbid_letters
+        .union(parse_letters(alphabet, args.unused.as_deref())?);
+
+    for path in &args.exclude_from {
+        constraints
+            .exclude
+            .extend(read_word_list(args, path, reports)?.words());
     }
+    Ok(constraints)
+}
 
-    fn add(mut self, c: LowerAsciiCharset) -> Self {
-        self.words[self.len as usize] = c;
-        self.len += 1;
-
-        self.charset.union(c);
-        self
+fn backend(args: &Args) -> Backend {
+    match args.solver {
+        Some(backend) => backend,
+        None if args.exact_cover => Backend::ExactCover,
+        None => Backend::Clique,
     }
 }
 
-fn show(mut w: Word) -> String {
-    w.iter_mut().for_each(|b| *b += b'a');
-    String::from_utf8_lossy(&w).into_owned()
+fn sharing(args: &Args) -> Sharing {
+    match (args.max_shared, args.max_repeats) {
+        (_, Some(k)) => Sharing::Total(k),
+        (k, None) => Sharing::Pairwise(k.unwrap_or(0)),
+    }
 }
 
-fn expand_anagrams<const N: usize>(
-    sols: &mut Vec<Sentence<N>>,
-    anagram_map: &FxHashMap<LowerAsciiCharset, Vec<Word>>,
-    sol: CharsetSentence<N>,
-) {
-    let mut a_idxs = vec![0; sol.len.into()];
-    let agrams = sol
-        .words
-        .iter()
-        .map(|w| anagram_map.get(w).unwrap())
-        .collect_vec();
-
-    loop {
-        /*
-        The idea is to maintain a vec of indexes into the anagram vector, so that a_idx[i] is the index into agrams[i].
-        We want to find all permutations of anagrams, each of which corresponds to a value for a_idx, so we just count
-        through all the possible values for a_idx by 'ticking' the first index, and let it overflow (or carry-over)
-        into the next indexes when necessary. We stop when the last index overflows.
-
-        We could also do it with five nested for-loops, but where's the fun in that? Also, it wouldn't generalise.
-        */
-        let sentence: Sentence<N> = a_idxs
-            .iter()
-            .enumerate()
-            .map(|(i, idx)| agrams[i][*idx])
-            .sorted()
-            .into();
-
-        sols.push(sentence);
-
-        let mut i = 0;
-        a_idxs[0] += 1;
-        while a_idxs[i] >= agrams[i].len() {
-            a_idxs[i] = 0;
-            i += 1;
-            if i >= a_idxs.len() {
-                return;
+/// A hash of the word lists and every option that changes the dictionary or graph, or `None` if a word list
+/// comes from stdin and so can't be hashed in advance.
+fn cache_key(args: &Args) -> io::Result<Option<u64>> {
+    let mut key = CacheKey::new();
+    for paths in [&args.dictionaries, &args.intersect, &args.exclude_from] {
+        key.add(paths.len());
+        for path in paths {
+            if path == Path::new(input::STDIN) {
+                return Ok(None);
             }
-            a_idxs[i] += 1;
+            key.add_file(path).map_err(|e| with_path(e, path))?;
         }
     }
-}
-
-fn find_sols<const N: usize>(
-    sols: &mut Vec<CharsetSentence<N>>,
-    cur_sol: CharsetSentence<N>,
-    last_added: LowerAsciiCharset,
-    nbs: &[LowerAsciiCharset],
-) {
-    let nbs = filter_vec(nbs, cur_sol.charset, last_added);
-    if nbs.is_empty() {
-        return;
+    // The frequencies only change the dictionary when they leave out rare words.
+    if let (Some(path), Some(min)) = (&args.frequencies, args.min_frequency) {
+        key.add_file(path).map_err(|e| with_path(e, path))?;
+        key.add(min.to_bits());
     }
+    key.add((&args.word_len, &args.alphabet))
+        .add((args.strip_accents, args.strip_punctuation))
+        .add((&args.include, &args.exclude, &args.require, &args.forbid))
+        .add((&args.unused, sharing(args), args.exact_cover, backend(args)));
+    Ok(Some(key.finish()))
+}
 
-    let sols_to_explore = nbs.iter().map(|&c| (c, cur_sol.add(c)));
-
-    if cur_sol.len + 1 >= N as u8 {
-        sols.extend(sols_to_explore.map(|(_, s)| s));
-    } else {
-        sols_to_explore.for_each(|(c, sol)| {
-            find_sols(sols, sol, c, &nbs);
-        });
+/// The words of the word lists which could be in a solution.
+fn dictionary<C: Charset>(
+    args: &Args,
+    constraints: &Constraints<C>,
+    freqs: Option<&Frequencies>,
+    reports: &mut Reports,
+) -> io::Result<Dictionary<C>> {
+    let mut report = Report::default();
+    let alphabet = args.alphabet.clone();
+    let mut dict = word_list(args, reports)?.to_dictionary_with::<C>(
+        alphabet,
+        args.word_len.clone(),
+        &mut report,
+    );
+    reports.push(("combined word list".to_string(), report));
+    dict.retain(|w| constraints.allows(w));
+    if let (Some(freqs), Some(min)) = (freqs, args.min_frequency) {
+        dict.retain(|w| freqs.get(w) >= min);
     }
+    Ok(dict)
 }
 
-type WordGraph = FxHashMap<LowerAsciiCharset, Vec<LowerAsciiCharset>>;
-fn build_graph(words: Vec<Word>) -> WordGraph {
-    words
-        .par_iter()
-        .map(|&w| {
-            let charset = LowerAsciiCharset::from(w);
-            let words: Vec<LowerAsciiCharset> = words
-                .iter()
-                .map(|w2| LowerAsciiCharset::from(*w2))
-                .filter(|c| !charset.intersects(*c))
-                .collect();
-            (w.into(), words)
-        })
-        .collect()
-}
+fn run<const N: usize, C: Charset>(args: &Args, goal: Goal) -> io::Result<()> {
+    let progress = |msg: &str| {
+        if !args.quiet {
+            eprint!("{msg}");
+        }
+    };
+    let done = || {
+        if !args.quiet {
+            eprintln!(" done!");
+        }
+    };
 
-fn anagram_groups() -> Vec<Vec<Word>> {
-    // For some reason, using BufReader here makes the rest of the program MUCH slower than using
-    // `include_bytes!()` unless I write to some other File.
-    // It doesn't make any sense; the output from this function is the same.
+    // For some reason, using BufReader to read the dictionary makes the rest of the program MUCH slower than
+    // using `include_bytes!()` unless I write to some other File.
+    // It doesn't make any sense; the output from the loading is the same.
     // I figure there must be some weird bottleneck with my OS.
     File::create("deleteme.txt")
         .and_then(|mut f| f.write_all(b"hi"))
         .ok();
     fs::remove_file("deleteme.txt").ok();
 
-    let f = File::open("words_alpha.txt").expect("Couldn't read words_alpha.txt");
-    BufReader::new(f)
-        .lines()
-        .flat_map(|line| line.expect("failed to read line").as_bytes().try_into())
-        .map(|mut w: Word| {
-            w.make_ascii_lowercase();
-
-            // It's more efficient to shift all the characters to be based on 'a' now and then undo it right at the end.
-            // Otherwise we would be doing this shift in the hottest path of the program (`LowerAsciiCharset::intersects`)
-            w.iter_mut().for_each(|b| *b -= b'a');
-
-            w
-        })
-        .filter(|w| distinct_letters(*w))
-        .sorted_unstable_by_key(|w| word_chars_sorted(*w))
-        .group_by(|w| word_chars_sorted(*w))
-        .into_iter()
-        .map(|(_, group)| group.collect())
-        .collect()
-}
+    let mut reports = vec![];
+    let constraints = constraints::<C>(args, &mut reports)?;
+    let freqs = match &args.frequencies {
+        Some(path) => {
+            Some(Frequencies::from_path(path, &args.alphabet).map_err(|e| with_path(e, path))?)
+        }
+        None => None,
+    };
+
+    let cache = match &args.cache {
+        Some(dir) => cache_key(args)?.map(|key| (Cache::new(dir), key)),
+        None => None,
+    };
+    let cached = match &cache {
+        Some((cache, key)) => {
+            progress("Loading cached word graph...");
+            let cached = cache.load::<C>(*key, &args.alphabet)?;
+            match cached {
+                Some(_) => done(),
+                None => progress(" not found.\n"),
+            }
+            cached
+        }
+        None => None,
+    };
+    let is_cached = cached.is_some();
+
+    let (dict, mut graph) = match cached {
+        Some(cached) => cached,
+        None => {
+            progress("Generating anagram maps...");
+            let dict = dictionary(args, &constraints, freqs.as_ref(), &mut reports)?;
+            done();
+            (dict, None)
+        }
+    };
+    if args.rank == Some(Ranking::Scrabble) && args.alphabet != Alphabet::english() {
+        return Err(invalid(
+            "Scrabble points are only known for the English alphabet",
+        ));
+    }
+    let scorer = match args.rank {
+        Some(ranking) => Some(
+            ranking
+                .scorer(freqs.as_ref())
+                .ok_or_else(|| invalid("ranking by frequency needs `--frequencies`"))?,
+        ),
+        None => None,
+    };
+    let seed = constraints.seed::<N>(&dict).map_err(invalid)?;
+
+    if args.report {
+        for (name, report) in &reports {
+            eprintln!("{name}: {report}");
+        }
+    }
 
-fn anagram_map(anagram_groups: &[Vec<Word>]) -> FxHashMap<Word, Vec<Word>> {
-    let mut out = FxHashMap::default();
-    for group in anagram_groups {
-        out.insert(group[0], group.to_vec());
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|e| with_path(e, path))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    // Words which clash with the included ones can never be used, so leave them out of the graph.
+    let words = || -> Vec<C> {
+        dict.representatives()
+            .filter(|c| constraints.sharing.allows(seed.words(), *c))
+            .collect()
+    };
+    let backend = backend(args);
+    let (letters, sharing) = (args.alphabet.charset(), constraints.sharing);
+    backend.check(letters, goal, sharing).map_err(invalid)?;
+    // Only the clique search's graph is worth caching, since it takes the longest to build.
+    if backend == Backend::Clique && graph.is_none() {
+        progress("Generating adjacency matrix...");
+        graph = Some(WordGraph::with_sharing(words(), sharing));
+        done();
+    }
+    if let (Some((cache, key)), false) = (&cache, is_cached) {
+        progress("Saving word graph to cache...");
+        cache.store(*key, &dict, graph.as_ref())?;
+        done();
+    }
+    let solver: Box<dyn Solver<N, C>> = match graph {
+        Some(graph) => Box::new(graph),
+        None => {
+            progress(&format!("Preparing the {backend} solver..."));
+            let solver = backend.solver(letters, words(), goal, sharing);
+            done();
+            solver.map_err(invalid)?
+        }
+    };
+
+    if args.count {
+        // Weight each solution by how many sentences it stands for, without ever building them.
+        let weight = |c| dict.anagrams(c).len() as u64;
+        progress("Counting solutions...");
+        let required = constraints.require_letters;
+        let count = solver.count(seed, goal, required, &weight);
+        done();
+        if let Some(header) = args.format.total_header() {
+            writeln!(out, "{header}")?;
+        }
+        writeln!(out, "{}", args.format.total(count))?;
+        return out.flush();
     }
-    out
-}
 
-fn distinct_letters(w: Word) -> bool {
-    let mut h = 0u64;
-    w.iter().all(|b| {
-        let x = 1 << b;
-        if x & h != 0 {
-            false
-        } else {
-            h |= x;
-            true
+    if let (Some(k), Some(scorer)) = (args.top, &scorer) {
+        // A set of anagram classes scores as well as its best sentence, so the best sentences all come from
+        // the best sets, although one set can have several of them.
+        let score = |c| {
+            let words = dict.anagrams(c).iter();
+            words
+                .map(|&w| scorer.word(w))
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        let combine = |scores: &[f64]| scorer.combine(scores);
+        progress("Finding the best solutions...");
+        let required = constraints.require_letters;
+        let sols = solver.best(seed, goal, required, k, &score, &combine);
+        let mut best = TopK::new(k);
+        for (_, sol) in sols {
+            dict.for_each_anagram(sol, |sentence| {
+                best.push(sentence.score(scorer.as_ref()), sentence)
+            });
         }
-    })
-}
+        done();
+
+        if let Some(header) = args.format.header::<N>() {
+            writeln!(out, "{header}")?;
+        }
+        for (_, sentence) in best.into_sorted_vec() {
+            let line = args.format.sentence(&sentence, &args.alphabet, false);
+            writeln!(out, "{line}")?;
+        }
+        return out.flush();
+    }
+
+    let header = match () {
+        _ if args.count_by_unused => args.format.count_header(),
+        _ if args.compact => args.format.compact_header::<N>(),
+        _ => args.format.header::<N>(),
+    };
+    if let Some(header) = header {
+        writeln!(out, "{header}")?;
+    }
+    let mut sorter = (args.sort || args.group_by_unused || scorer.is_some())
+        .then(|| ExternalSorter::new((args.sort_memory as usize) << 20));
+    let mut counts = BTreeMap::new();
+
+    progress("Finding solutions...");
+    thread::scope(|scope| -> io::Result<()> {
+        let (tx, rx) = mpsc::sync_channel::<Batch<N, C>>(SOLUTION_BATCHES);
+        let (dict, solver) = (&dict, &solver);
+        scope.spawn(move || {
+            // Expand the anagrams here so that it's spread over the worker threads. If the receiver has gone
+            // then writing the output failed, so there's no point carrying on.
+            let send = |sol| {
+                let mut sentences = vec![];
+                if !args.compact {
+                    dict.expand_anagrams(&mut sentences, sol);
+                }
+                match tx.send((sol, sentences)) {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(_) => ControlFlow::Break(()),
+                }
+            };
+            let required = constraints.require_letters;
+            solver.complete_each(seed, goal, required, &send)
+        });
+
+        let mut write = |line: String| match &mut sorter {
+            Some(sorter) => sorter.push(line),
+            None => writeln!(out, "{line}"),
+        };
+        for (sol, sentences) in rx {
+            if args.compact {
+                write(args.format.compact(&sol, dict, args.group_by_unused))?;
+                continue;
+            }
+            for sentence in sentences {
+                if args.count_by_unused {
+                    let unused = sentence.unused_letters::<C>(&args.alphabet);
+                    *counts.entry(unused).or_insert(0usize) += 1;
+                } else {
+                    let mut line =
+                        args.format
+                            .sentence(&sentence, &args.alphabet, args.group_by_unused);
+                    if let Some(scorer) = &scorer {
+                        // Negating the bits of a non-negative float sorts it in descending order.
+                        let key = !sentence.score(scorer.as_ref()).to_bits();
+                        line = format!("{key:0width$x} {line}", width = RANK_KEY_LEN - 1);
+                    }
+                    write(line)?;
+                }
+            }
+        }
+        Ok(())
+    })?;
+    done();
 
-fn word_chars_sorted(w: Word) -> u64 {
-    // concatenate the sorted bytes into a u64
-    w.iter()
-        .sorted()
-        .enumerate()
-        .fold(0u64, |out, (i, b)| out | ((*b as u64) << (i * 8)))
+    for (unused, count) in counts {
+        writeln!(out, "{}", args.format.count(unused, &args.alphabet, count))?;
+    }
+    if let Some(sorter) = sorter {
+        let key_len = if scorer.is_some() { RANK_KEY_LEN } else { 0 };
+        sorter.finish_with(&mut out, |line| &line[key_len..])?;
+    }
+    out.flush()
 }
 
-fn main() {
-    eprint!("Generating anagram maps...");
-    let anagrams = anagram_groups();
-    let anagram_map = anagram_map(&anagrams);
-    let anagram_reps = anagram_map.keys().copied().collect_vec();
-    eprintln!(" done!");
-
-    eprint!("Generating adjacency matrix...");
-    let graph = build_graph(anagram_reps);
-    eprintln!(" done!");
-
-    eprint!("Finding solutions modulo anagram...");
-    let sols: Vec<CharsetSentence<SLEN>> = graph
-        .par_iter()
-        .map(|(w, nbs)| {
-            let mut sols = vec![];
-            let init = CharsetSentence::<SLEN>::new().add(*w);
-            find_sols(&mut sols, init, *w, nbs);
-            sols
-        })
-        .flatten()
-        .collect();
-    eprintln!(" done!");
-
-    eprint!("Expanding anagram solutions...");
-    let mut sols_with_agrams = vec![];
-    let anagram_map_by_charset: FxHashMap<LowerAsciiCharset, Vec<Word>> = anagram_map
-        .into_iter()
-        .map(|(k, v)| (k.into(), v))
-        .collect();
-    for sol in sols {
-        expand_anagrams::<SLEN>(&mut sols_with_agrams, &anagram_map_by_charset, sol);
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let alphabet_len = args.alphabet.len();
+    let min_word_len = *args.word_len.start();
+    // Letters can be used again when words may share them, so more words can fit.
+    let (room, repeats) = match sharing(&args) {
+        Sharing::Pairwise(0) => (Some(alphabet_len), 0),
+        Sharing::Pairwise(_) => (None, 0),
+        Sharing::Total(k) => (Some(alphabet_len + k as usize), k),
+    };
+    let most_words = (room.unwrap_or(alphabet_len) / min_word_len).max(1);
+    // Solutions can't have more words than `run` is instantiated for, so rather than quietly missing the
+    // longer ones, make the limit explicit.
+    let by_letters = args.letters.is_some() || args.exact_cover;
+    if by_letters && args.sentence_len.is_none() && most_words > MAX_SENTENCE_LEN {
+        eprintln!(
+            "error: up to {most_words} words of {min_word_len} letters could fit, but solutions can have at most {MAX_SENTENCE_LEN}; pass `--sentence-len` to search for fewer"
+        );
+        return ExitCode::FAILURE;
+    }
+    let (goal, sentence_len) = match args.letters {
+        Some(letters) if letters as usize > alphabet_len => {
+            eprintln!("error: the alphabet only has {alphabet_len} letters, not {letters}");
+            return ExitCode::FAILURE;
+        }
+        Some(letters) => (
+            Goal::Letters(letters),
+            args.sentence_len.map_or(most_words, usize::from),
+        ),
+        None if args.exact_cover => (
+            Goal::Letters(alphabet_len as u32),
+            args.sentence_len.map_or(most_words, usize::from),
+        ),
+        None => {
+            let sentence_len = args.sentence_len.map_or(5, usize::from);
+            if room.is_some_and(|room| min_word_len * sentence_len > room) {
+                let repeats = match repeats {
+                    0 => String::new(),
+                    k => format!(" and {k} repeats"),
+                };
+                eprintln!(
+                    "error: {sentence_len} words of {min_word_len} letters need more than {alphabet_len} distinct letters{repeats}"
+                );
+                return ExitCode::FAILURE;
+            }
+            (Goal::Words, sentence_len)
+        }
+    };
+
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("failed to initialise the thread pool");
     }
-    eprintln!(" done!");
 
-    for sol in sols_with_agrams.iter().sorted() {
-        println!("{}", sol.as_string());
+    let res = with_const!(
+        sentence_len,
+        N,
+        [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13],
+        // Only alphabets too big for the narrow charset pay for the wide one.
+        if alphabet_len <= LowerAsciiCharset::CAPACITY {
+            run::<N, LowerAsciiCharset>(&args, goal)
+        } else {
+            run::<N, WideCharset>(&args, goal)
+        }
+    );
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        // e.g. when piped into `head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
     }
 }
@@ -0,0 +1,205 @@
+//! Find sets of words which share no letters by building most of each solution one word at a time and then
+//! looking up the last two words all at once, among every pair of words which share no letters.
+//!
+//! The pairs are stored by the letters they use together, so once the other words have been chosen, the pairs
+//! which complete them are exactly the ones using the letters which are left. That only works when it's known
+//! which letters are left, so this is for solutions of a fixed number of words of a single length, which leave
+//! a known number of letters unused. Each way of choosing the unused letters is searched in turn, which is
+//! cheap when there are few of them (e.g. one for five five-letter words out of 26 letters) but grows quickly
+//! with more.
+//!
+//! Only the last two words are looked up. Looking up the last four as a pair using the rarest letter left
+//! plus a pair using the rest means going through every pair using that letter, and there are so many more of
+//! those than single words using it that choosing the first two words one at a time is several times faster.
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    charset::{Charset, LowerAsciiCharset, WideCharset, ALPHABET_LEN},
+    rarest_first::Rarity,
+    sentence::CharsetSentence,
+    solver::{branch, Split},
+};
+
+pub struct MeetInTheMiddle<C = LowerAsciiCharset> {
+    letters: C,
+    rarity: Rarity<C>,
+    /// Every pair of words which share no letters, by the letters they use together.
+    pairs: FxHashMap<C, Vec<(C, C)>>,
+    /// The length of every word, or `None` if they don't all have the same length.
+    word_len: Option<usize>,
+}
+
+impl MeetInTheMiddle {
+    /// Search the English alphabet with `words`.
+    pub fn new(words: impl IntoIterator<Item = LowerAsciiCharset>) -> Self {
+        Self::with_letters(LowerAsciiCharset::first(ALPHABET_LEN), words)
+    }
+}
+
+impl<C: Charset> MeetInTheMiddle<C> {
+    /// Search `letters` with `words`, e.g. every letter of some other [`crate::Alphabet`].
+    pub fn with_letters(letters: C, words: impl IntoIterator<Item = C>) -> Self {
+        let mut words: Vec<_> = words
+            .into_iter()
+            .filter(|c| !c.is_empty() && letters.is_superset(*c))
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+
+        let rarity = Rarity::new(letters, &words);
+
+        let pairs = words
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(i, &a)| {
+                words[i + 1..]
+                    .iter()
+                    .filter(move |b| !a.intersects(**b))
+                    .map(move |&b| (a, b))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .into_group_map_by(|&(a, b)| {
+                let mut union = a;
+                union.union(b);
+                union
+            })
+            .into_iter()
+            .collect();
+
+        let mut lens = words.iter().map(|c| c.len() as usize);
+        let first = lens.next().unwrap_or(0);
+        let word_len = lens.all(|len| len == first).then_some(first);
+        Self {
+            letters,
+            rarity,
+            pairs,
+            word_len,
+        }
+    }
+
+    /// Whether the words all have the same length, which the search needs.
+    pub fn is_supported(&self) -> bool {
+        self.word_len.is_some()
+    }
+
+    /// Find every set of `N` words that share no letters, modulo anagram.
+    ///
+    /// # Panics
+    ///
+    /// If the words don't all have the same length.
+    pub fn search<const N: usize>(&self) -> Vec<CharsetSentence<N, C>> {
+        self.complete(CharsetSentence::new(), C::default())
+    }
+
+    /// Find every way of adding words to `seed` to make `N` words that share no letters and use all of the
+    /// `required` letters, modulo anagram.
+    ///
+    /// # Panics
+    ///
+    /// If the words don't all have the same length.
+    pub fn complete<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        required: C,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.split(seed, required).complete()
+    }
+
+    /// Split up the search [`Self::complete`] does, with a branch for each of [`Self::starts`].
+    pub(crate) fn split<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        required: C,
+    ) -> Split<'_, N, C> {
+        let branches = self
+            .starts(seed, required)
+            .into_iter()
+            .map(|(sol, letters)| branch(move |mut emit| self.find_sols(&mut emit, sol, letters)))
+            .collect();
+        Split {
+            sols: vec![],
+            branches,
+        }
+    }
+
+    /// The places to search from in parallel: for each choice of letters to leave unused, each way of adding
+    /// the first word to `seed`, along with the letters the solution will use.
+    fn starts<const N: usize>(
+        &self,
+        seed: CharsetSentence<N, C>,
+        required: C,
+    ) -> Vec<(CharsetSentence<N, C>, C)> {
+        let word_len = self
+            .word_len
+            .expect("can only search words which all have the same length");
+        let used = seed.charset().len() as usize + (N - seed.len()) * word_len;
+        let Some(skips) = (self.letters.len() as usize).checked_sub(used) else {
+            return vec![];
+        };
+        // Any letters can be left unused except the seed's own and the required ones.
+        let mut keep = seed.charset();
+        keep.union(required);
+        let optional: Vec<u8> = self.letters.difference(keep).letters().collect();
+
+        let mut starts = vec![];
+        for unused in optional.into_iter().combinations(skips) {
+            let bits = unused.iter().fold(0, |bits, &l| bits | 1 << l);
+            let letters = self
+                .letters
+                .difference(C::from_wide(WideCharset::from_bits(bits)));
+            if N - seed.len() <= 2 {
+                starts.push((seed, letters));
+            } else {
+                self.branch(seed, letters, |sol| starts.push((sol, letters)));
+            }
+        }
+        starts
+    }
+
+    /// Call `f` with `sol` plus each of the words which use the rarest of `letters` it doesn't use yet (which
+    /// some word has to) and no letters outside of `letters`.
+    fn branch<const N: usize>(
+        &self,
+        sol: CharsetSentence<N, C>,
+        letters: C,
+        mut f: impl FnMut(CharsetSentence<N, C>),
+    ) {
+        let left = letters.difference(sol.charset());
+        let Some(letter) = self.rarity.rarest(left) else {
+            return;
+        };
+        for &c in &self.rarity.by_rarest[letter as usize] {
+            if left.is_superset(c) {
+                f(sol.add(c));
+            }
+        }
+    }
+
+    fn find_sols<const N: usize>(
+        &self,
+        emit: &mut impl FnMut(CharsetSentence<N, C>),
+        sol: CharsetSentence<N, C>,
+        letters: C,
+    ) {
+        let left = letters.difference(sol.charset());
+        match N - sol.len() {
+            0 => emit(sol),
+            // The last word uses exactly the letters which are left, if there is one.
+            1 => self.branch(sol, letters, |sol| {
+                if sol.charset() == letters {
+                    emit(sol);
+                }
+            }),
+            2 => {
+                for &(a, b) in self.pairs.get(&left).map_or(&[][..], Vec::as_slice) {
+                    emit(sol.add(a).add(b));
+                }
+            }
+            _ => self.branch(sol, letters, |sol| self.find_sols(emit, sol, letters)),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Cleaning up the lines of word lists before they're parsed, and reporting the ones which can't be used.
+
+use std::{borrow::Cow, collections::BTreeMap, fmt};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::{
+    alphabet::Alphabet,
+    word::{Word, MAX_WORD_LEN},
+};
+
+/// How to clean up a line of a word list before parsing it. Letters are always lowercased.
+#[derive(Clone, Debug, Default)]
+pub struct Normaliser {
+    /// The letters words can be made of.
+    pub alphabet: Alphabet,
+    /// Take the accents off letters which aren't in the alphabet, e.g. `café` becomes `cafe` in English but
+    /// `schön` stays as it is in German.
+    pub strip_accents: bool,
+    /// Remove everything which isn't a letter or a digit, e.g. `don't` becomes `dont`.
+    pub strip_punctuation: bool,
+}
+
+impl Normaliser {
+    /// Clean up `line` and parse it, recording the outcome in `report`.
+    pub fn parse(&self, line: &str, report: &mut Report) -> Option<Word> {
+        let normalised = self.normalise(line);
+        match self.classify(&normalised) {
+            Ok(w) => {
+                report.accept(normalised != line || line.chars().any(char::is_uppercase));
+                Some(w)
+            }
+            Err(reason) => {
+                report.reject(reason, line);
+                None
+            }
+        }
+    }
+
+    pub fn normalise<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        if self.strip_accents && line.chars().any(|c| !self.alphabet.contains(c)) {
+            // Decomposing splits accented letters into the plain letter followed by combining marks.
+            line = line
+                .chars()
+                .flat_map(|c| match self.alphabet.contains(c) {
+                    true => vec![c],
+                    false => c.nfd().filter(|c| !is_combining_mark(*c)).collect(),
+                })
+                .collect();
+        }
+        if self.strip_punctuation && line.chars().any(|c| !c.is_alphanumeric()) {
+            line = line.chars().filter(|c| c.is_alphanumeric()).collect();
+        }
+        line
+    }
+
+    fn classify(&self, line: &str) -> Result<Word, Rejection> {
+        if line.is_empty() {
+            Err(Rejection::Blank)
+        } else if !line.chars().all(char::is_alphabetic) {
+            Err(Rejection::NotLetters)
+        } else if !line.chars().all(|c| self.alphabet.contains(c)) {
+            Err(Rejection::OutsideAlphabet)
+        } else {
+            self.alphabet.parse(line).ok_or(Rejection::TooLong)
+        }
+    }
+}
+
+/// Why a line of a word list (or a word from it) wasn't used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rejection {
+    Blank,
+    /// Contains characters other than letters, e.g. digits, punctuation or spaces.
+    NotLetters,
+    /// Contains letters which aren't in the alphabet, e.g. accented letters in English.
+    OutsideAlphabet,
+    /// Too long to be a [`Word`] at all.
+    TooLong,
+    /// A length that wasn't asked for.
+    WrongLength,
+    RepeatedLetters,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Rejection::Blank => "blank".to_string(),
+            Rejection::NotLetters => "containing digits, punctuation or spaces".to_string(),
+            Rejection::OutsideAlphabet => "with letters outside the alphabet".to_string(),
+            Rejection::TooLong => format!("longer than {MAX_WORD_LEN} letters"),
+            Rejection::WrongLength => "of the wrong length".to_string(),
+            Rejection::RepeatedLetters => "with repeated letters".to_string(),
+        };
+        f.write_str(&reason)
+    }
+}
+
+/// How many lines (or words) were read, used and rejected, with an example of each kind of rejection.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub read: usize,
+    /// Used, but only after being lowercased or otherwise normalised.
+    pub normalised: usize,
+    pub rejected: BTreeMap<Rejection, (usize, String)>,
+}
+
+impl Report {
+    pub fn accept(&mut self, normalised: bool) {
+        self.read += 1;
+        self.normalised += normalised as usize;
+    }
+
+    pub fn reject(&mut self, reason: Rejection, example: &str) {
+        self.read += 1;
+        self.rejected
+            .entry(reason)
+            .or_insert_with(|| (0, example.to_string()))
+            .0 += 1;
+    }
+
+    pub fn used(&self) -> usize {
+        self.read - self.rejected.values().map(|(n, _)| n).sum::<usize>()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} read, {} used", self.read, self.used())?;
+        if self.normalised > 0 {
+            write!(f, " ({} normalised)", self.normalised)?;
+        }
+        for (reason, (count, example)) in &self.rejected {
+            write!(f, "\n  {count} {reason}, e.g. {example:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{charset::LowerAsciiCharset, word_list::WordList};
+
+    #[test]
+    fn rejections() {
+        let normaliser = Normaliser::default();
+        let too_long = "a".repeat(MAX_WORD_LEN + 1);
+        let lines = [
+            ("fjord", None),
+            ("Waltz", None),
+            ("", Some(Rejection::Blank)),
+            ("don't", Some(Rejection::NotLetters)),
+            ("two words", Some(Rejection::NotLetters)),
+            ("abc123", Some(Rejection::NotLetters)),
+            ("café", Some(Rejection::OutsideAlphabet)),
+            ("ωμέγα", Some(Rejection::OutsideAlphabet)),
+            (&too_long, Some(Rejection::TooLong)),
+        ];
+        let mut report = Report::default();
+        for (line, reason) in lines {
+            let mut one = Report::default();
+            let parsed = normaliser.parse(line, &mut one);
+            assert_eq!(parsed.is_some(), reason.is_none(), "{line:?}");
+            assert_eq!(one.rejected.keys().next().copied(), reason, "{line:?}");
+            normaliser.parse(line, &mut report);
+        }
+        assert_eq!(report.read, lines.len());
+        assert_eq!(report.used(), 2);
+        assert_eq!(report.normalised, 1);
+        assert_eq!(
+            report.rejected[&Rejection::NotLetters],
+            (3, "don't".to_string())
+        );
+    }
+
+    #[test]
+    fn stripping() {
+        let normaliser = Normaliser {
+            strip_accents: true,
+            strip_punctuation: true,
+            ..Default::default()
+        };
+        let mut report = Report::default();
+        for line in ["café", "don't", "Naïve"] {
+            assert!(normaliser.parse(line, &mut report).is_some(), "{line:?}");
+        }
+        assert_eq!((report.used(), report.normalised), (3, 3));
+        assert!(normaliser.parse("abc123", &mut report).is_none());
+        assert_eq!(report.rejected[&Rejection::NotLetters].0, 1);
+    }
+
+    /// Words with the wrong length or repeated letters are only rejected once a dictionary is made from them,
+    /// and are counted once however many times they're listed.
+    #[test]
+    fn dictionary_rejections() {
+        let list =
+            WordList::from_reader(&b"fjord\nfjord\nwaltz\nvibes\nlevel\nlevel\nfjords\n"[..])
+                .unwrap();
+        let mut report = Report::default();
+        let dict =
+            list.to_dictionary_with::<LowerAsciiCharset>(Alphabet::english(), 5..=5, &mut report);
+        assert_eq!(dict.len(), 3);
+        assert_eq!((report.read, report.used()), (5, 3));
+        assert_eq!(
+            report.rejected[&Rejection::WrongLength],
+            (1, "fjords".to_string())
+        );
+        assert_eq!(
+            report.rejected[&Rejection::RepeatedLetters],
+            (1, "level".to_string())
+        );
+    }
+}
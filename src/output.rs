@@ -0,0 +1,204 @@
+//! Formats for writing solutions out, one line each.
+//!
+//! Words only ever contain letters, so nothing needs escaping or quoting. The structured
+//! formats put the unused letters first, so sorting the lines groups the solutions by them.
+
+use itertools::Itertools;
+use std::{fmt, str::FromStr};
+
+use crate::{
+    alphabet::Alphabet,
+    charset::{Charset, WideCharset},
+    dictionary::Dictionary,
+    graph::Count,
+    sentence::{CharsetSentence, Sentence},
+    word::word_chars_sorted,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The words separated by spaces.
+    #[default]
+    Text,
+    /// One JSON object per line, with the unused letters, the words, their anagram classes (each word's
+    /// letters in alphabetical order) and all of the letters used.
+    Jsonl,
+    /// The unused letters followed by one column per word, with a header row.
+    Csv,
+}
+
+impl Format {
+    /// The line to write before any solutions of up to `N` words, if any.
+    pub fn header<const N: usize>(&self) -> Option<String> {
+        match self {
+            Format::Text | Format::Jsonl => None,
+            Format::Csv => Some(
+                ["unused".to_string()]
+                    .into_iter()
+                    .chain((1..=N).map(|i| format!("word{i}")))
+                    .join(","),
+            ),
+        }
+    }
+
+    /// The line to write for `sentence`, whose words are made of letters from `alphabet`. Text only
+    /// includes the unused letters if `with_unused`.
+    pub fn sentence<const N: usize>(
+        &self,
+        sentence: &Sentence<N>,
+        alphabet: &Alphabet,
+        with_unused: bool,
+    ) -> String {
+        // Every alphabet fits in a wide charset, and this isn't part of the search.
+        let unused = alphabet.show_charset(sentence.unused_letters::<WideCharset>(alphabet));
+        let show = |w| alphabet.show(w);
+        match self {
+            Format::Text if with_unused => format!("{unused} {}", alphabet.show_sentence(sentence)),
+            Format::Text => alphabet.show_sentence(sentence),
+            Format::Jsonl => format!(
+                r#"{{"unused":"{unused}","words":[{}],"classes":[{}],"charset":"{}"}}"#,
+                sentence
+                    .words()
+                    .map(|w| format!("\"{}\"", show(w)))
+                    .join(","),
+                sentence
+                    .words()
+                    .map(|w| format!("\"{}\"", alphabet.show(word_chars_sorted(w))))
+                    .join(","),
+                alphabet.show_charset(sentence.charset::<WideCharset>()),
+            ),
+            Format::Csv => {
+                // Pad shorter sentences out so that every row has the same number of columns.
+                let words = sentence.words().map(show).pad_using(N, |_| String::new());
+                [unused.to_string()].into_iter().chain(words).join(",")
+            }
+        }
+    }
+
+    /// The line to write for `sol` without expanding its anagrams: each of its words is replaced by its whole
+    /// anagram class, followed by the number of sentences that represents.
+    pub fn compact<const N: usize, C: Charset>(
+        &self,
+        sol: &CharsetSentence<N, C>,
+        dict: &Dictionary<C>,
+        with_unused: bool,
+    ) -> String {
+        let alphabet = dict.alphabet();
+        let show = |w| alphabet.show(w);
+        let unused = alphabet.show_charset(sol.unused_letters(alphabet));
+        let count = dict.count_anagrams(sol);
+        // Order the classes like the words of the sentences they expand to.
+        let groups = dict
+            .anagram_groups(sol)
+            .into_iter()
+            .map(|group| group.iter().copied().map(show).sorted().collect_vec())
+            .sorted()
+            .collect_vec();
+        match self {
+            Format::Text => {
+                let groups = groups
+                    .iter()
+                    .map(|group| format!("{{{}}}", group.join(",")))
+                    .join(" ");
+                match with_unused {
+                    true => format!("{unused} {groups} {count}"),
+                    false => format!("{groups} {count}"),
+                }
+            }
+            Format::Jsonl => format!(
+                r#"{{"unused":"{unused}","groups":[{}],"count":{count},"charset":"{}"}}"#,
+                groups
+                    .iter()
+                    .map(|group| format!(
+                        "[{}]",
+                        group.iter().map(|w| format!("\"{w}\"")).join(",")
+                    ))
+                    .join(","),
+                alphabet.show_charset(sol.charset()),
+            ),
+            Format::Csv => {
+                let groups = groups
+                    .iter()
+                    .map(|group| group.join(" "))
+                    .pad_using(N, |_| String::new());
+                [unused.to_string(), count.to_string()]
+                    .into_iter()
+                    .chain(groups)
+                    .join(",")
+            }
+        }
+    }
+
+    /// The line to write before any compact solutions of up to `N` words, if any.
+    pub fn compact_header<const N: usize>(&self) -> Option<String> {
+        match self {
+            Format::Text | Format::Jsonl => None,
+            Format::Csv => Some(
+                ["unused".to_string(), "count".to_string()]
+                    .into_iter()
+                    .chain((1..=N).map(|i| format!("word{i}")))
+                    .join(","),
+            ),
+        }
+    }
+
+    /// The line to write for `count` solutions leaving `unused` unused.
+    pub fn count(&self, unused: impl Charset, alphabet: &Alphabet, count: usize) -> String {
+        let unused = alphabet.show_charset(unused);
+        match self {
+            Format::Text => format!("{unused} {count}"),
+            Format::Jsonl => format!(r#"{{"unused":"{unused}","count":{count}}}"#),
+            Format::Csv => format!("{unused},{count}"),
+        }
+    }
+
+    /// The line to write for the totals of a whole search.
+    pub fn total(&self, count: Count) -> String {
+        let Count { sets, sentences } = count;
+        match self {
+            Format::Text => format!("{sentences} solutions ({sets} modulo anagram)"),
+            Format::Jsonl => format!(r#"{{"solutions":{sentences},"sets":{sets}}}"#),
+            Format::Csv => format!("{sentences},{sets}"),
+        }
+    }
+
+    /// The line to write before the totals, if any.
+    pub fn total_header(&self) -> Option<String> {
+        match self {
+            Format::Text | Format::Jsonl => None,
+            Format::Csv => Some("solutions,sets".to_string()),
+        }
+    }
+
+    /// The line to write before any counts, if any.
+    pub fn count_header(&self) -> Option<String> {
+        match self {
+            Format::Text | Format::Jsonl => None,
+            Format::Csv => Some("unused,count".to_string()),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "jsonl" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            _ => Err(UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownFormat(pub String);
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` isn't one of `text`, `jsonl` or `csv`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFormat {}
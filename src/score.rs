@@ -0,0 +1,197 @@
+//! Scoring sentences by their words, so that only the best few solutions need to be found.
+
+use std::{cmp::Reverse, collections::BinaryHeap, fmt, str::FromStr};
+
+use crate::{
+    frequency::{Familiarity, Frequencies},
+    word::Word,
+};
+
+/// A score for each word, and a way of combining the scores of a sentence's words into one.
+pub trait Scorer: Sync {
+    fn word(&self, w: Word) -> f64;
+
+    /// The score of a sentence whose words have the given scores. This must never decrease when one of the
+    /// scores increases, which is what lets a search skip branches that can't beat the best solutions so far.
+    fn combine(&self, scores: &[f64]) -> f64;
+}
+
+/// How common the words are, combined as in [`Familiarity`].
+pub struct ByFrequency<'a>(pub &'a Frequencies, pub Familiarity);
+
+impl Scorer for ByFrequency<'_> {
+    fn word(&self, w: Word) -> f64 {
+        self.0.get(w)
+    }
+
+    fn combine(&self, scores: &[f64]) -> f64 {
+        match self.1 {
+            Familiarity::Min => scores.iter().copied().fold(f64::INFINITY, f64::min),
+            Familiarity::GeometricMean => {
+                // Summing logs doesn't overflow, and any unknown word makes it -inf, so the mean is 0.
+                let sum: f64 = scores.iter().map(|f| f.ln()).sum();
+                (sum / scores.len() as f64).exp()
+            }
+        }
+    }
+}
+
+/// The total number of letters.
+pub struct Length;
+
+impl Scorer for Length {
+    fn word(&self, w: Word) -> f64 {
+        w.len() as f64
+    }
+
+    fn combine(&self, scores: &[f64]) -> f64 {
+        scores.iter().sum()
+    }
+}
+
+/// The total value of the words' letters in English Scrabble.
+pub struct ScrabblePoints;
+
+/// The value of each letter of the English alphabet in Scrabble.
+const SCRABBLE_POINTS: [u8; 26] = [
+    1, 3, 3, 2, 1, 4, 2, 4, 1, 8, 5, 1, 3, 1, 1, 3, 10, 1, 1, 1, 1, 4, 4, 8, 4, 10,
+];
+
+impl Scorer for ScrabblePoints {
+    fn word(&self, w: Word) -> f64 {
+        w.letters()
+            .iter()
+            .map(|&l| SCRABBLE_POINTS.get(l as usize).copied().unwrap_or(0) as f64)
+            .sum()
+    }
+
+    fn combine(&self, scores: &[f64]) -> f64 {
+        scores.iter().sum()
+    }
+}
+
+/// Which [`Scorer`] to rank solutions by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ranking {
+    /// [`ByFrequency`], which needs a list of [`Frequencies`].
+    Frequency(Familiarity),
+    Length,
+    Scrabble,
+}
+
+impl Ranking {
+    /// The scorer to rank by, or `None` if it needs `freqs` and there aren't any.
+    pub fn scorer<'a>(&self, freqs: Option<&'a Frequencies>) -> Option<Box<dyn Scorer + 'a>> {
+        Some(match *self {
+            Ranking::Frequency(by) => Box::new(ByFrequency(freqs?, by)),
+            Ranking::Length => Box::new(Length),
+            Ranking::Scrabble => Box::new(ScrabblePoints),
+        })
+    }
+}
+
+impl FromStr for Ranking {
+    type Err = UnknownRanking;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "length" => Ok(Ranking::Length),
+            "scrabble" => Ok(Ranking::Scrabble),
+            _ => match s.parse() {
+                Ok(by) => Ok(Ranking::Frequency(by)),
+                Err(_) => Err(UnknownRanking(s.to_string())),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownRanking(pub String);
+
+impl fmt::Display for UnknownRanking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` isn't one of `min`, `mean`, `length` or `scrabble`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownRanking {}
+
+/// The `k` highest-scoring items seen so far. Ties are broken arbitrarily.
+#[derive(Clone, Debug)]
+pub struct TopK<T> {
+    k: usize,
+    // The worst of the best is on top, so that it's the one which gets replaced.
+    heap: BinaryHeap<Reverse<Scored<T>>>,
+}
+
+impl<T> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn push(&mut self, score: f64, item: T) {
+        if self.floor().is_some_and(|floor| score <= floor) {
+            return;
+        }
+        self.heap.push(Reverse(Scored(score, item)));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// The score an item has to beat to be kept, once there are `k` of them.
+    pub fn floor(&self) -> Option<f64> {
+        match self.heap.len() >= self.k {
+            true => Some(self.heap.peek().map_or(f64::INFINITY, |Reverse(s)| s.0)),
+            false => None,
+        }
+    }
+
+    /// The best `k` items of both.
+    pub fn merge(mut self, other: Self) -> Self {
+        for Reverse(Scored(score, item)) in other.heap {
+            self.push(score, item);
+        }
+        self
+    }
+
+    /// The items with their scores, best first.
+    pub fn into_sorted_vec(self) -> Vec<(f64, T)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(Scored(score, item))| (score, item))
+            .collect()
+    }
+}
+
+/// An item which is compared only by its score.
+#[derive(Clone, Debug)]
+struct Scored<T>(f64, T);
+
+impl<T> PartialEq for Scored<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0).is_eq()
+    }
+}
+
+impl<T> Eq for Scored<T> {}
+
+impl<T> PartialOrd for Scored<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Scored<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
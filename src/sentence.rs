@@ -0,0 +1,130 @@
+use itertools::Itertools;
+
+use crate::{
+    alphabet::Alphabet,
+    charset::{Charset, LowerAsciiCharset},
+    score::Scorer,
+    word::{show, Word},
+};
+
+/// A set of up to `N` words.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Sentence<const N: usize> {
+    words: [Option<Word>; N],
+    len: u8,
+}
+
+impl<const N: usize> Sentence<N> {
+    pub fn new() -> Sentence<N> {
+        Sentence {
+            len: 0,
+            words: [None; N],
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, w: Word) -> Self {
+        self.words[self.len as usize] = Some(w);
+        self.len += 1;
+        self
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = Word> + '_ {
+        self.words.iter().flatten().copied()
+    }
+
+    /// The words separated by spaces, assuming they're English (see [`Alphabet::show_sentence`] otherwise).
+    pub fn as_string(&self) -> String {
+        self.words().map(show).join(" ")
+    }
+
+    /// The union of the charsets of all the words.
+    pub fn charset<C: Charset>(&self) -> C {
+        let mut charset = C::default();
+        self.words().for_each(|w| charset.union(w.into()));
+        charset
+    }
+
+    /// The letters of `alphabet` which none of the words use.
+    pub fn unused_letters<C: Charset>(&self, alphabet: &Alphabet) -> C {
+        alphabet.charset::<C>().difference(self.charset())
+    }
+
+    /// The score of the sentence according to `scorer`.
+    pub fn score(&self, scorer: &(impl Scorer + ?Sized)) -> f64 {
+        let scores: Vec<_> = self.words().map(|w| scorer.word(w)).collect();
+        scorer.combine(&scores)
+    }
+}
+
+impl<const N: usize> Default for Sentence<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, const N: usize> From<I> for Sentence<N>
+where
+    I: IntoIterator<Item = Word>,
+{
+    fn from(words: I) -> Self {
+        let mut out = Self::new();
+        words.into_iter().for_each(|w| out = out.add(w));
+        out
+    }
+}
+
+/// A set of up to `N` words, each represented only by its charset (i.e. modulo anagram).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct CharsetSentence<const N: usize, C = LowerAsciiCharset> {
+    words: [C; N],
+    len: u8,
+    charset: C,
+}
+
+impl<const N: usize, C: Charset> CharsetSentence<N, C> {
+    pub fn new() -> CharsetSentence<N, C> {
+        CharsetSentence {
+            len: 0,
+            words: [C::default(); N],
+            charset: C::default(),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, c: C) -> Self {
+        self.words[self.len as usize] = c;
+        self.len += 1;
+
+        self.charset.union(c);
+        self
+    }
+
+    pub fn words(&self) -> &[C] {
+        &self.words[..self.len as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The union of the charsets of all the words.
+    pub fn charset(&self) -> C {
+        self.charset
+    }
+
+    /// The letters of `alphabet` which none of the words use.
+    pub fn unused_letters(&self, alphabet: &Alphabet) -> C {
+        alphabet.charset::<C>().difference(self.charset)
+    }
+}
+
+impl<const N: usize, C: Charset> Default for CharsetSentence<N, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
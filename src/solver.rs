@@ -0,0 +1,351 @@
+//! A common interface to the different ways of searching for solutions, so that they can be chosen between by
+//! name and checked against each other.
+//!
+//! Every [`Solver`] is built from the anagram representatives of a dictionary (see [`Backend::solver`]) and
+//! finds solutions as sets of charsets, which can then be expanded into sentences with
+//! [`Dictionary::expand_anagrams`](crate::Dictionary::expand_anagrams) whichever one found them.
+
+use rayon::prelude::*;
+use std::{error::Error, fmt, ops::ControlFlow, str::FromStr, sync::Mutex};
+
+use crate::{
+    bitset_graph::BitsetGraph,
+    charset::Charset,
+    exact_cover::ExactCover,
+    graph::{Count, Goal, Sharing, WordGraph},
+    meet_in_the_middle::MeetInTheMiddle,
+    rarest_first::RarestFirst,
+    score::TopK,
+    sentence::CharsetSentence,
+};
+
+/// A search for sets of up to `N` words which share no letters (or as many as the solver was built to allow).
+///
+/// Solvers only have to say how to split a search into parts which can run in parallel, with [`Self::split`];
+/// everything else is built on that, though solvers which can do better (e.g. count solutions without finding
+/// each one) can override it.
+///
+/// The `goal` passed to each method must be one the solver was built for, as checked by [`Backend::check`].
+pub trait Solver<const N: usize, C: Charset>: Sync {
+    /// Split up the search for every way of adding words to `seed` so that they satisfy `goal` and use all of
+    /// the `required` letters, modulo anagram.
+    fn split(&self, seed: CharsetSentence<N, C>, goal: Goal, required: C) -> Split<'_, N, C>;
+
+    /// Collect the solutions [`Self::split`] would find, in no particular order.
+    fn complete(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+    ) -> Vec<CharsetSentence<N, C>> {
+        self.split(seed, goal, required).complete()
+    }
+
+    /// Call `f` with each solution [`Self::split`] would find as soon as it's found. `f` is called from many
+    /// threads at once, and the search stops early once it returns [`ControlFlow::Break`].
+    fn complete_each(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        f: &(dyn Fn(CharsetSentence<N, C>) -> ControlFlow<()> + Sync),
+    ) -> ControlFlow<()> {
+        self.split(seed, goal, required).each(f)
+    }
+
+    /// Count the solutions [`Self::split`] would find, weighting each one by the product of its words'
+    /// `weight`s.
+    fn count(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        weight: &(dyn Fn(C) -> u64 + Sync),
+    ) -> Count {
+        self.split(seed, goal, required).count(weight)
+    }
+
+    /// Find the `k` best solutions, best first, as [`WordGraph::best`] does. By default every solution is
+    /// scored, which is fine for searches that find few of them.
+    #[allow(clippy::too_many_arguments)]
+    fn best(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        k: usize,
+        score: &(dyn Fn(C) -> f64 + Sync),
+        combine: &(dyn Fn(&[f64]) -> f64 + Sync),
+    ) -> Vec<(f64, CharsetSentence<N, C>)> {
+        let top = Mutex::new(TopK::new(k));
+        let _ = self.complete_each(seed, goal, required, &|sol| {
+            let scores: Vec<_> = sol.words().iter().map(|&c| score(c)).collect();
+            top.lock().unwrap().push(combine(&scores), sol);
+            ControlFlow::Continue(())
+        });
+        top.into_inner().unwrap().into_sorted_vec()
+    }
+}
+
+/// A search split up into parts which can run in parallel, as returned by [`Solver::split`].
+pub struct Split<'a, const N: usize, C> {
+    /// The solutions found while splitting it up, e.g. the seed itself.
+    pub sols: Vec<CharsetSentence<N, C>>,
+    /// The rest of the search.
+    pub branches: Vec<Branch<'a, N, C>>,
+}
+
+/// Part of a [`Split`] search, which calls the function it's given with each solution it finds.
+pub type Branch<'a, const N: usize, C> =
+    Box<dyn FnOnce(&mut dyn FnMut(CharsetSentence<N, C>)) + Send + 'a>;
+
+/// Box up `f` as a [`Branch`].
+pub(crate) fn branch<'a, const N: usize, C>(
+    f: impl FnOnce(&mut dyn FnMut(CharsetSentence<N, C>)) + Send + 'a,
+) -> Branch<'a, N, C> {
+    Box::new(f)
+}
+
+impl<const N: usize, C: Charset> Split<'_, N, C> {
+    /// Collect the solutions, in no particular order.
+    pub fn complete(self) -> Vec<CharsetSentence<N, C>> {
+        let mut sols = self.sols;
+        sols.par_extend(self.branches.into_par_iter().flat_map_iter(|branch| {
+            let mut sols = vec![];
+            branch(&mut |sol| sols.push(sol));
+            sols
+        }));
+        sols
+    }
+
+    /// Call `f` with each solution as soon as it's found, as [`Solver::complete_each`] does.
+    pub fn each(
+        self,
+        f: impl Fn(CharsetSentence<N, C>) -> ControlFlow<()> + Sync,
+    ) -> ControlFlow<()> {
+        self.sols.into_iter().try_for_each(&f)?;
+        self.branches.into_par_iter().try_for_each(|branch| {
+            // Only checked between branches, so that the hot loop doesn't have to.
+            let mut flow = ControlFlow::Continue(());
+            branch(&mut |sol| {
+                if flow.is_continue() {
+                    flow = f(sol);
+                }
+            });
+            flow
+        })
+    }
+
+    /// Count the solutions, weighting each one as [`Solver::count`] does.
+    pub fn count(self, weight: impl Fn(C) -> u64 + Sync) -> Count {
+        let weigh = |sol: &CharsetSentence<N, C>| sol.words().iter().map(|w| weight(*w)).product();
+        let mut count = Count::default();
+        self.sols.iter().for_each(|sol| count.add_sol(weigh(sol)));
+        count
+            + self
+                .branches
+                .into_par_iter()
+                .map(|branch| {
+                    let mut count = Count::default();
+                    branch(&mut |sol| count.add_sol(weigh(&sol)));
+                    count
+                })
+                .sum::<Count>()
+    }
+}
+
+impl<const N: usize, C: Charset> Solver<N, C> for WordGraph<C> {
+    fn split(&self, seed: CharsetSentence<N, C>, goal: Goal, required: C) -> Split<'_, N, C> {
+        self.split_among(seed, &self.candidates(seed), goal, required)
+    }
+
+    fn count(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        weight: &(dyn Fn(C) -> u64 + Sync),
+    ) -> Count {
+        WordGraph::count(self, seed, goal, required, weight)
+    }
+
+    fn best(
+        &self,
+        seed: CharsetSentence<N, C>,
+        goal: Goal,
+        required: C,
+        k: usize,
+        score: &(dyn Fn(C) -> f64 + Sync),
+        combine: &(dyn Fn(&[f64]) -> f64 + Sync),
+    ) -> Vec<(f64, CharsetSentence<N, C>)> {
+        WordGraph::best(self, seed, goal, required, k, score, combine)
+    }
+}
+
+impl<const N: usize, C: Charset> Solver<N, C> for BitsetGraph<C> {
+    fn split(&self, seed: CharsetSentence<N, C>, goal: Goal, required: C) -> Split<'_, N, C> {
+        BitsetGraph::split(self, seed, goal, required)
+    }
+}
+
+impl<const N: usize, C: Charset> Solver<N, C> for RarestFirst<C> {
+    fn split(&self, seed: CharsetSentence<N, C>, goal: Goal, required: C) -> Split<'_, N, C> {
+        RarestFirst::split(self, seed, goal, required)
+    }
+}
+
+// Meet in the middle only ever finds `N` words, and exact covers only ever use every letter, so both of them
+// can ignore the goal.
+
+impl<const N: usize, C: Charset> Solver<N, C> for MeetInTheMiddle<C> {
+    fn split(&self, seed: CharsetSentence<N, C>, _goal: Goal, required: C) -> Split<'_, N, C> {
+        MeetInTheMiddle::split(self, seed, required)
+    }
+}
+
+impl<const N: usize, C: Charset> Solver<N, C> for ExactCover<C> {
+    fn split(&self, seed: CharsetSentence<N, C>, _goal: Goal, _required: C) -> Split<'_, N, C> {
+        ExactCover::split(self, seed)
+    }
+
+    fn count(
+        &self,
+        seed: CharsetSentence<N, C>,
+        _goal: Goal,
+        _required: C,
+        weight: &(dyn Fn(C) -> u64 + Sync),
+    ) -> Count {
+        ExactCover::count(self, seed, weight)
+    }
+}
+
+/// Which [`Solver`] to search with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Grow cliques in a [`WordGraph`], which keeps each word's neighbours as a list.
+    #[default]
+    Clique,
+    /// Grow cliques in a [`BitsetGraph`], which keeps them as a bit matrix.
+    Bitset,
+    /// Work through the alphabet from the rarest letter with [`RarestFirst`].
+    RarestFirst,
+    /// Look up the last two words among every pair with [`MeetInTheMiddle`].
+    MeetInTheMiddle,
+    /// Cover every letter exactly once with [`ExactCover`].
+    ExactCover,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 5] = [
+        Backend::Clique,
+        Backend::Bitset,
+        Backend::RarestFirst,
+        Backend::MeetInTheMiddle,
+        Backend::ExactCover,
+    ];
+
+    /// The name it's chosen by, e.g. on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Clique => "clique",
+            Backend::Bitset => "bitset",
+            Backend::RarestFirst => "rarest-first",
+            Backend::MeetInTheMiddle => "meet-in-the-middle",
+            Backend::ExactCover => "exact-cover",
+        }
+    }
+
+    /// Whether this backend can search `letters` for solutions which satisfy `goal` with `sharing`.
+    pub fn check<C: Charset>(
+        &self,
+        letters: C,
+        goal: Goal,
+        sharing: Sharing,
+    ) -> Result<(), Unsupported> {
+        let unsupported = Err(Unsupported(*self));
+        match self {
+            Backend::Clique | Backend::Bitset => Ok(()),
+            _ if !sharing.is_strict() => unsupported,
+            Backend::RarestFirst => Ok(()),
+            Backend::MeetInTheMiddle if goal != Goal::Words => unsupported,
+            Backend::ExactCover if goal != Goal::Letters(letters.len()) => unsupported,
+            Backend::MeetInTheMiddle | Backend::ExactCover => Ok(()),
+        }
+    }
+
+    /// Build a solver for this backend from `words`, the anagram representatives to search `letters` with.
+    pub fn solver<const N: usize, C: Charset>(
+        &self,
+        letters: C,
+        words: Vec<C>,
+        goal: Goal,
+        sharing: Sharing,
+    ) -> Result<Box<dyn Solver<N, C>>, Unsupported> {
+        self.check(letters, goal, sharing)?;
+        Ok(match self {
+            Backend::Clique => Box::new(WordGraph::with_sharing(words, sharing)),
+            Backend::Bitset => Box::new(BitsetGraph::with_sharing(words, sharing)),
+            Backend::RarestFirst => Box::new(RarestFirst::with_letters(letters, words)),
+            Backend::MeetInTheMiddle => {
+                let solver = MeetInTheMiddle::with_letters(letters, words);
+                if !solver.is_supported() {
+                    return Err(Unsupported(*self));
+                }
+                Box::new(solver)
+            }
+            Backend::ExactCover => Box::new(ExactCover::with_letters(letters, words)),
+        })
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Backend {
+    type Err = UnknownBackend;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Backend::ALL
+            .into_iter()
+            .find(|b| b.name() == s)
+            .ok_or_else(|| UnknownBackend(s.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownBackend(pub String);
+
+impl fmt::Display for UnknownBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` isn't one of `clique`, `bitset`, `rarest-first`, `meet-in-the-middle` or `exact-cover`",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownBackend {}
+
+/// A backend was asked for solutions it can't find.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unsupported(pub Backend);
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let finds = match self.0 {
+            Backend::Clique | Backend::Bitset => "any solution",
+            Backend::RarestFirst => "solutions whose words share no letters",
+            Backend::MeetInTheMiddle => {
+                "a fixed number of words of a single length which share no letters"
+            }
+            Backend::ExactCover => "solutions which use every letter exactly once",
+        };
+        write!(f, "the `{}` solver only finds {finds}", self.0)
+    }
+}
+
+impl Error for Unsupported {}
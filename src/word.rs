@@ -0,0 +1,101 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::charset::ALPHABET_LEN;
+
+/// The longest word that can have no repeated letters.
+pub const MAX_WORD_LEN: usize = ALPHABET_LEN;
+
+/// A word whose letters have been shifted to start at zero (i.e. `b'a'` is stored as `0`).
+///
+/// The letters are stored inline so that words (and sentences of them) stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Word {
+    letters: [u8; MAX_WORD_LEN],
+    len: u8,
+}
+
+impl Word {
+    /// Build a word from letters which are already based on zero, or `None` if it's too long.
+    pub fn from_letters(letters: &[u8]) -> Option<Word> {
+        if letters.len() > MAX_WORD_LEN {
+            return None;
+        }
+        let mut w = Word {
+            letters: [0; MAX_WORD_LEN],
+            len: letters.len() as u8,
+        };
+        w.letters[..letters.len()].copy_from_slice(letters);
+        Some(w)
+    }
+
+    pub fn letters(&self) -> &[u8] {
+        &self.letters[..self.len as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Ord for Word {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.letters().cmp(other.letters())
+    }
+}
+
+impl PartialOrd for Word {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", show(*self))
+    }
+}
+
+/// Parse a line of a word list into a [`Word`], or `None` if it's too long to have distinct letters or has
+/// anything other than ASCII letters in it (see [`crate::normalise`] to clean lines up first).
+pub fn parse(line: &str) -> Option<Word> {
+    if !line.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut w = Word::from_letters(line.as_bytes())?;
+    w.letters.make_ascii_lowercase();
+
+    // It's more efficient to shift all the characters to be based on 'a' now and then undo it right at the end.
+    // Otherwise we would be doing this shift in the hottest path of the program (`LowerAsciiCharset::intersects`)
+    let len = w.len();
+    w.letters[..len].iter_mut().for_each(|b| *b -= b'a');
+
+    Some(w)
+}
+
+pub fn show(w: Word) -> String {
+    w.letters().iter().map(|b| (b + b'a') as char).collect()
+}
+
+pub fn distinct_letters(w: Word) -> bool {
+    let mut h = 0u64;
+    w.letters().iter().all(|b| {
+        let x = 1 << b;
+        if x & h != 0 {
+            false
+        } else {
+            h |= x;
+            true
+        }
+    })
+}
+
+/// The letters of `w` in sorted order, which is the same for every anagram of `w`.
+pub fn word_chars_sorted(mut w: Word) -> Word {
+    let len = w.len();
+    w.letters[..len].sort_unstable();
+    w
+}
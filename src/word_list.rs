@@ -0,0 +1,176 @@
+//! Word lists read from files, which can be combined before building a [`Dictionary`].
+
+use itertools::Itertools;
+use rustc_hash::FxHashSet;
+use std::{
+    io::{self, BufRead},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use crate::{
+    alphabet::Alphabet,
+    charset::Charset,
+    dictionary::Dictionary,
+    input,
+    normalise::{Normaliser, Rejection, Report},
+    word::{distinct_letters, Word},
+};
+
+/// A set of words, of any length and possibly with repeated letters. The words don't remember which
+/// [`Alphabet`] they were read with, so lists read with different ones shouldn't be combined.
+#[derive(Clone, Debug, Default)]
+pub struct WordList {
+    words: FxHashSet<Word>,
+}
+
+impl WordList {
+    /// Read a word list with one word per line, which may be compressed or `-` for stdin (see
+    /// [`input::open`]). Lines which aren't made of English letters are skipped.
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_path_with(path, &Normaliser::default(), &mut Report::default())
+    }
+
+    /// Like [`Self::from_path`], but cleaning up each line with `normaliser` first and recording what
+    /// happened to it in `report`.
+    pub fn from_path_with(
+        path: impl AsRef<Path>,
+        normaliser: &Normaliser,
+        report: &mut Report,
+    ) -> io::Result<Self> {
+        Self::from_reader_with(input::open(path)?, normaliser, report)
+    }
+
+    /// Read a word list with one word per line. Lines which aren't made of English letters are skipped.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        Self::from_reader_with(reader, &Normaliser::default(), &mut Report::default())
+    }
+
+    /// Like [`Self::from_reader`], but cleaning up each line with `normaliser` first and recording what
+    /// happened to it in `report`.
+    pub fn from_reader_with(
+        reader: impl BufRead,
+        normaliser: &Normaliser,
+        report: &mut Report,
+    ) -> io::Result<Self> {
+        let mut words = FxHashSet::default();
+        for line in reader.lines() {
+            words.extend(normaliser.parse(&line?, report));
+        }
+        Ok(Self { words })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn contains(&self, w: Word) -> bool {
+        self.words.contains(&w)
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = Word> + '_ {
+        self.words.iter().copied()
+    }
+
+    /// Add every word of `other`.
+    pub fn union(&mut self, other: &WordList) {
+        self.words.extend(other.words())
+    }
+
+    /// Only keep the words which are also in `other`.
+    pub fn intersect(&mut self, other: &WordList) {
+        self.words.retain(|w| other.contains(*w))
+    }
+
+    /// Remove every word of `other`.
+    pub fn subtract(&mut self, other: &WordList) {
+        self.words.retain(|w| !other.contains(*w))
+    }
+
+    /// Group the English words with a length in `word_lens` into a dictionary.
+    pub fn to_dictionary(&self, word_lens: RangeInclusive<usize>) -> Dictionary {
+        self.to_dictionary_with(Alphabet::english(), word_lens, &mut Report::default())
+    }
+
+    /// Like [`Self::to_dictionary`], but for words read with `alphabet`, and recording which words were left
+    /// out and why in `report`.
+    pub fn to_dictionary_with<C: Charset>(
+        &self,
+        alphabet: Alphabet,
+        word_lens: RangeInclusive<usize>,
+        report: &mut Report,
+    ) -> Dictionary<C> {
+        let words = self.words().sorted_unstable().filter(|&w| {
+            let reason = match () {
+                _ if !word_lens.contains(&w.len()) => Rejection::WrongLength,
+                _ if !distinct_letters(w) => Rejection::RepeatedLetters,
+                _ => {
+                    report.accept(false);
+                    return true;
+                }
+            };
+            report.reject(reason, &alphabet.show(w));
+            false
+        });
+        let words = words.collect_vec();
+        Dictionary::from_words_in(alphabet, words)
+    }
+}
+
+impl FromIterator<Word> for WordList {
+    fn from_iter<I: IntoIterator<Item = Word>>(words: I) -> Self {
+        Self {
+            words: words.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word;
+
+    fn list(words: &str) -> WordList {
+        WordList::from_reader(words.replace(' ', "\n").as_bytes()).unwrap()
+    }
+
+    fn sorted(list: &WordList) -> Vec<String> {
+        let alphabet = Alphabet::english();
+        list.words().map(|w| alphabet.show(w)).sorted().collect()
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = list("fjord waltz gucks vibex");
+        let b = list("waltz nymph vibex chunk");
+
+        let mut union = a.clone();
+        union.union(&b);
+        assert_eq!(
+            sorted(&union),
+            ["chunk", "fjord", "gucks", "nymph", "vibex", "waltz"]
+        );
+
+        let mut intersection = a.clone();
+        intersection.intersect(&b);
+        assert_eq!(sorted(&intersection), ["vibex", "waltz"]);
+
+        let mut difference = a.clone();
+        difference.subtract(&b);
+        assert_eq!(sorted(&difference), ["fjord", "gucks"]);
+        assert!(!difference.contains(word::parse("waltz").unwrap()));
+
+        // Starting from nothing, only union adds anything.
+        let mut empty = WordList::default();
+        empty.intersect(&a);
+        assert!(empty.is_empty());
+        empty.union(&a);
+        assert_eq!(sorted(&empty), sorted(&a));
+        empty.subtract(&a);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+//! Checks that every solver finds exactly the same solutions as the others, for each kind of search it supports.
+
+use five_clique::{
+    word, Backend, Charset, CharsetSentence, Count, Goal, LowerAsciiCharset, Sharing, WideCharset,
+};
+use std::fs;
+
+/// The anagram classes of every `step`th word of `words_five.txt`, cut down to each of `lens` letters in turn,
+/// leaving out any with repeated letters.
+fn words<C: Charset>(step: usize, lens: &[usize]) -> Vec<C> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/words_five.txt");
+    let list = fs::read_to_string(path).unwrap();
+    let mut words: Vec<C> = list
+        .lines()
+        .step_by(step)
+        .zip(lens.iter().cycle())
+        .filter_map(|(line, &len)| word::parse(&line[..len]))
+        .filter(|w| w.len() as u32 == C::from(*w).len())
+        .map(C::from)
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+fn charset<C: Charset>(s: &str) -> C {
+    C::from(word::parse(s).unwrap())
+}
+
+struct Case<C> {
+    letters: C,
+    words: Vec<C>,
+    goal: Goal,
+    sharing: Sharing,
+    seed: Vec<C>,
+    required: C,
+}
+
+impl<C: Charset> Case<C> {
+    fn new(letters: C, words: Vec<C>, goal: Goal) -> Self {
+        Self {
+            letters,
+            words,
+            goal,
+            sharing: Sharing::default(),
+            seed: vec![],
+            required: C::default(),
+        }
+    }
+
+    /// Run every backend which supports this case, and check that they all agree and that what they find is
+    /// actually a solution. Returns the backends that ran and the number of solutions.
+    fn check<const N: usize>(&self) -> (Vec<Backend>, usize) {
+        let mut seed = CharsetSentence::<N, C>::new();
+        for &c in &self.seed {
+            seed = seed.add(c);
+        }
+        let weight = |c: C| c.len() as u64;
+
+        let mut results: Vec<(Backend, Vec<Vec<C>>, Count)> = vec![];
+        for backend in Backend::ALL {
+            let solver = match backend.solver::<N, C>(
+                self.letters,
+                self.words.clone(),
+                self.goal,
+                self.sharing,
+            ) {
+                Ok(solver) => solver,
+                Err(_) => continue,
+            };
+            let mut sols: Vec<Vec<C>> = solver
+                .complete(seed, self.goal, self.required)
+                .into_iter()
+                .map(|sol| {
+                    self.assert_valid(&sol, seed);
+                    let mut words = sol.words().to_vec();
+                    words.sort_unstable();
+                    words
+                })
+                .collect();
+            sols.sort_unstable();
+            let len = sols.len();
+            sols.dedup();
+            assert_eq!(sols.len(), len, "{backend} found a solution twice");
+
+            let count = solver.count(seed, self.goal, self.required, &weight);
+            assert_eq!(count.sets, sols.len() as u64, "{backend} miscounted");
+            results.push((backend, sols, count));
+        }
+
+        let (first, sols, count) = &results[0];
+        for (backend, other_sols, other_count) in &results[1..] {
+            assert_eq!(other_sols, sols, "{backend} and {first} disagree");
+            assert_eq!(
+                other_count, count,
+                "{backend} and {first} count differently"
+            );
+        }
+        let backends = results.iter().map(|(backend, _, _)| *backend).collect();
+        (backends, sols.len())
+    }
+
+    fn assert_valid<const N: usize>(
+        &self,
+        sol: &CharsetSentence<N, C>,
+        seed: CharsetSentence<N, C>,
+    ) {
+        assert!(
+            self.goal.is_met(sol),
+            "{sol:?} doesn't meet {:?}",
+            self.goal
+        );
+        assert!(sol.charset().is_superset(self.required));
+        assert!(self.letters.is_superset(sol.charset()));
+        assert_eq!(&sol.words()[..seed.len()], seed.words());
+        for (i, &w) in sol.words().iter().enumerate() {
+            assert!(
+                self.sharing.allows(&sol.words()[..i], w),
+                "{sol:?} shares too many letters"
+            );
+        }
+    }
+}
+
+#[test]
+fn fixed_number_of_words() {
+    let case = Case::new(LowerAsciiCharset::first(26), words(25, &[5]), Goal::Words);
+    let (backends, sols) = case.check::<4>();
+    assert_eq!(
+        backends,
+        [
+            Backend::Clique,
+            Backend::Bitset,
+            Backend::RarestFirst,
+            Backend::MeetInTheMiddle
+        ]
+    );
+    assert!(sols > 0);
+}
+
+#[test]
+fn fixed_number_of_words_in_wide_charsets() {
+    let case = Case::new(WideCharset::first(26), words(25, &[5]), Goal::Words);
+    let (backends, sols) = case.check::<4>();
+    assert_eq!(backends.len(), 4);
+    assert!(sols > 0);
+}
+
+#[test]
+fn seed_and_required_letters() {
+    let mut case = Case::new(LowerAsciiCharset::first(26), words(20, &[5]), Goal::Words);
+    case.seed = vec![charset("fjord")];
+    case.required = charset("qu");
+    let (backends, sols) = case.check::<4>();
+    assert_eq!(backends.len(), 4);
+    assert!(sols > 0);
+}
+
+#[test]
+fn number_of_letters() {
+    let case = Case::new(
+        LowerAsciiCharset::first(26),
+        words(40, &[3, 4, 5]),
+        Goal::Letters(21),
+    );
+    let (backends, sols) = case.check::<6>();
+    assert_eq!(
+        backends,
+        [Backend::Clique, Backend::Bitset, Backend::RarestFirst]
+    );
+    assert!(sols > 0);
+}
+
+#[test]
+fn exact_covers() {
+    // Exact covers of the whole alphabet are rare, so cover the first 15 letters instead.
+    let letters = LowerAsciiCharset::first(15);
+    let words = words(1, &[5, 4, 3])
+        .into_iter()
+        .filter(|c| letters.is_superset(*c))
+        .collect();
+    let case = Case::new(letters, words, Goal::Letters(15));
+    let (backends, sols) = case.check::<5>();
+    assert_eq!(
+        backends,
+        [
+            Backend::Clique,
+            Backend::Bitset,
+            Backend::RarestFirst,
+            Backend::ExactCover
+        ]
+    );
+    assert!(sols > 0);
+}
+
+#[test]
+fn exact_covers_of_a_fixed_number_of_words() {
+    let letters = LowerAsciiCharset::first(15);
+    let words = words(1, &[5])
+        .into_iter()
+        .filter(|c| letters.is_superset(*c))
+        .collect();
+    let case = Case::new(letters, words, Goal::Words);
+    let (backends, sols) = case.check::<3>();
+    assert_eq!(backends.len(), 4);
+    assert!(sols > 0);
+}
+
+#[test]
+fn shared_letters() {
+    // Including words no longer than the number of letters they can share, which could go with themselves.
+    let cases = [
+        (40, &[5][..], Sharing::Pairwise(1)),
+        (40, &[5], Sharing::Total(2)),
+        (100, &[2, 3], Sharing::Pairwise(2)),
+        (100, &[2, 3], Sharing::Total(3)),
+    ];
+    for (step, lens, sharing) in cases {
+        let mut case = Case::new(LowerAsciiCharset::first(26), words(step, lens), Goal::Words);
+        case.sharing = sharing;
+        let (backends, sols) = case.check::<3>();
+        assert_eq!(backends, [Backend::Clique, Backend::Bitset]);
+        assert!(sols > 0);
+    }
+}
+
+/// Combines the scores of a solution's words.
+type Combine = dyn Fn(&[f64]) -> f64 + Sync;
+
+/// Check that every backend's `best` finds the same scores, and solutions with those scores, as sorting all
+/// of the solutions `complete` finds.
+fn check_best<const N: usize, C: Charset>(case: &Case<C>, combine: &Combine) {
+    // Arbitrary but repeatable scores, with few enough values that some solutions tie.
+    let score = |c: C| (c.letters().fold(7u32, |h, l| h.wrapping_mul(31) ^ l as u32) % 97) as f64;
+    let seed = CharsetSentence::<N, C>::new();
+    for backend in Backend::ALL {
+        let Ok(solver) =
+            backend.solver::<N, C>(case.letters, case.words.clone(), case.goal, case.sharing)
+        else {
+            continue;
+        };
+        let all = solver.complete(seed, case.goal, case.required);
+        let scored = |sol: &CharsetSentence<N, C>| {
+            let scores: Vec<_> = sol.words().iter().map(|&c| score(c)).collect();
+            combine(&scores)
+        };
+        let mut expected: Vec<f64> = all.iter().map(scored).collect();
+        expected.sort_unstable_by(|a, b| b.total_cmp(a));
+        expected.truncate(10);
+        assert!(!expected.is_empty());
+
+        let best = solver.best(seed, case.goal, case.required, 10, &score, combine);
+        let scores: Vec<f64> = best.iter().map(|(score, _)| *score).collect();
+        assert_eq!(
+            scores, expected,
+            "{backend} missed some of the best solutions"
+        );
+        for (score, sol) in &best {
+            assert!(
+                all.contains(sol),
+                "{backend} found {sol:?}, which isn't a solution"
+            );
+            assert_eq!(scored(sol), *score);
+        }
+    }
+}
+
+#[test]
+fn best_solutions() {
+    let min = |s: &[f64]| s.iter().copied().fold(f64::INFINITY, f64::min);
+    let mean = |s: &[f64]| s.iter().sum::<f64>() / s.len() as f64;
+    let sum = |s: &[f64]| s.iter().sum::<f64>();
+    let combiners: [&Combine; 3] = [&min, &mean, &sum];
+
+    let fixed = Case::new(LowerAsciiCharset::first(26), words(25, &[5]), Goal::Words);
+    let letters = Case::new(
+        LowerAsciiCharset::first(26),
+        words(40, &[3, 4, 5]),
+        Goal::Letters(21),
+    );
+    for combine in combiners {
+        check_best::<4, _>(&fixed, combine);
+        check_best::<6, _>(&letters, combine);
+    }
+}
+
+#[test]
+fn names() {
+    for backend in Backend::ALL {
+        assert_eq!(backend.name().parse::<Backend>(), Ok(backend));
+    }
+    assert!("lists".parse::<Backend>().is_err());
+}
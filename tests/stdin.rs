@@ -0,0 +1,49 @@
+//! Checks that word lists can be piped in on stdin, compressed or not.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Run the binary on `-`, feeding it `input`, and return the solutions it writes in order.
+fn run(args: &[&str], input: &[u8]) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_five_clique"))
+        .args(["-q", "--sort"])
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout.lines().map(String::from).collect()
+}
+
+const WORDS: &str = "fjord\nwaltz\nvibex\nchord\n";
+
+const EXPECTED: [&str; 2] = ["chord vibex waltz", "fjord vibex waltz"];
+
+#[test]
+fn plain() {
+    assert_eq!(run(&["-s", "3"], WORDS.as_bytes()), EXPECTED);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip() {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut gz = GzEncoder::new(vec![], Compression::default());
+    gz.write_all(WORDS.as_bytes()).unwrap();
+    assert_eq!(run(&["-s", "3"], &gz.finish().unwrap()), EXPECTED);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() {
+    let zst = zstd::encode_all(WORDS.as_bytes(), 0).unwrap();
+    assert_eq!(run(&["-s", "3"], &zst), EXPECTED);
+}